@@ -0,0 +1,284 @@
+use crate::log::{KeyPress, Pause, Test, TimedTest};
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Gaps between keypresses longer than this are breaks rather than typing,
+/// and are left out of latencies.
+pub const PAUSE_THRESHOLD_MS: i64 = 2000;
+
+/// Typing statistics for one key.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct KeyStats {
+    /// Times the key was expected.
+    pub expected: usize,
+    /// Times the key was expected but a different key was typed.
+    pub missed: usize,
+    /// Times the key was typed.
+    pub typed: usize,
+    /// Times the key was typed when a different key was expected.
+    pub mistyped: usize,
+    latency_total_ms: i64,
+    latency_count: usize,
+}
+
+impl KeyStats {
+    /// Fraction of the times the key was expected that it was missed.
+    pub fn miss_rate(&self) -> Option<f64> {
+        (self.expected > 0).then(|| self.missed as f64 / self.expected as f64)
+    }
+
+    /// Fraction of the times the key was typed that it was wrong.
+    pub fn mistype_rate(&self) -> Option<f64> {
+        (self.typed > 0).then(|| self.mistyped as f64 / self.typed as f64)
+    }
+
+    /// Mean time from the previous keypress to a correct press of this key.
+    pub fn mean_latency_ms(&self) -> Option<f64> {
+        (self.latency_count > 0).then(|| self.latency_total_ms as f64 / self.latency_count as f64)
+    }
+}
+
+/// Key on a US keyboard which types a character, ignoring case and shift.
+pub fn base_key(c: char) -> char {
+    const SHIFTED: &str = "~!@#$%^&*()_+{}|:\"<>?";
+    const UNSHIFTED: &str = "`1234567890-=[]\\;',./";
+    match SHIFTED.find(c) {
+        Some(i) => UNSHIFTED.chars().nth(i).unwrap(),
+        None => c.to_ascii_lowercase(),
+    }
+}
+
+/// Computes statistics for every key in the log, keyed by [`base_key`].
+///
+/// Mistakes are attributed both to the key which was typed and, when it is
+/// known, to the key which was expected.
+pub fn key_stats(keypresses: &[KeyPress]) -> HashMap<char, KeyStats> {
+    let mut stats: HashMap<char, KeyStats> = HashMap::new();
+    let mut previous: Option<DateTime<Utc>> = None;
+    for k in keypresses {
+        let typed = stats.entry(base_key(k.key)).or_default();
+        typed.typed += 1;
+        if !k.correct {
+            typed.mistyped += 1;
+        }
+
+        if let Some(expected) = k.expected() {
+            let expected = stats.entry(base_key(expected)).or_default();
+            expected.expected += 1;
+            if k.correct {
+                if let Some(previous) = previous {
+                    let latency = k.time - previous;
+                    if latency < Duration::milliseconds(PAUSE_THRESHOLD_MS) {
+                        expected.latency_total_ms += latency.num_milliseconds();
+                        expected.latency_count += 1;
+                    }
+                }
+            } else {
+                expected.missed += 1;
+            }
+        }
+        previous = Some(k.time);
+    }
+    stats
+}
+
+/// Fewest attempts at an n-gram for it to be ranked among the slowest.
+pub const MIN_NGRAM_ATTEMPTS: usize = 3;
+
+/// Typing statistics for a sequence of characters.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NgramStats {
+    pub ngram: String,
+    /// Times the last character was typed after the others.
+    pub attempts: usize,
+    /// Times the last character was missed after the others.
+    pub errors: usize,
+    pub error_rate: f64,
+    /// Time from the first to the last keypress of the n-gram.
+    pub mean_latency_ms: Option<f64>,
+    pub median_latency_ms: Option<f64>,
+}
+
+/// Computes statistics for every sequence of `n` characters typed.
+///
+/// An n-gram is only counted when its first characters were typed correctly
+/// without a pause longer than [`PAUSE_THRESHOLD_MS`] between any of them.
+pub fn ngram_stats(keypresses: &[KeyPress], n: usize) -> Vec<NgramStats> {
+    let mut ngrams: HashMap<String, (usize, usize, Vec<i64>)> = HashMap::new();
+    // Expected characters and times of the correct keypresses leading up to this one.
+    let mut context: Vec<(char, DateTime<Utc>)> = Vec::new();
+    let mut previous: Option<DateTime<Utc>> = None;
+    for k in keypresses {
+        if previous.is_some_and(|p| k.time - p >= Duration::milliseconds(PAUSE_THRESHOLD_MS)) {
+            context.clear();
+        }
+        previous = Some(k.time);
+        let Some(expected) = k.expected() else {
+            context.clear();
+            continue;
+        };
+
+        if n > 0 && context.len() >= n - 1 {
+            let start = context.len() + 1 - n;
+            let mut ngram = context[start..].iter().map(|&(c, _)| c).collect::<String>();
+            ngram.push(expected);
+            let (attempts, errors, latencies) = ngrams.entry(ngram).or_default();
+            *attempts += 1;
+            if k.correct {
+                let first = context.get(start).map_or(k.time, |&(_, time)| time);
+                latencies.push((k.time - first).num_milliseconds());
+            } else {
+                *errors += 1;
+            }
+        }
+
+        if k.correct {
+            context.push((expected, k.time));
+            if context.len() >= n {
+                context.remove(0);
+            }
+        } else {
+            context.clear();
+        }
+    }
+
+    ngrams
+        .into_iter()
+        .map(|(ngram, (attempts, errors, mut latencies))| {
+            latencies.sort_unstable();
+            NgramStats {
+                ngram,
+                attempts,
+                errors,
+                error_rate: errors as f64 / attempts as f64,
+                mean_latency_ms: (!latencies.is_empty())
+                    .then(|| latencies.iter().sum::<i64>() as f64 / latencies.len() as f64),
+                median_latency_ms: match latencies.len() {
+                    0 => None,
+                    len if len % 2 == 0 => {
+                        Some((latencies[len / 2 - 1] + latencies[len / 2]) as f64 / 2.0)
+                    }
+                    len => Some(latencies[len / 2] as f64),
+                },
+            }
+        })
+        .collect()
+}
+
+/// The `count` n-grams with the highest mean latency, among those attempted
+/// at least [`MIN_NGRAM_ATTEMPTS`] times.
+pub fn slowest_ngrams(keypresses: &[KeyPress], n: usize, count: usize) -> Vec<NgramStats> {
+    let mut ngrams = ngram_stats(keypresses, n)
+        .into_iter()
+        .filter(|s| s.attempts >= MIN_NGRAM_ATTEMPTS && s.mean_latency_ms.is_some())
+        .collect::<Vec<_>>();
+    ngrams.sort_by(|a, b| {
+        b.mean_latency_ms
+            .partial_cmp(&a.mean_latency_ms)
+            .unwrap()
+            .then_with(|| a.ngram.cmp(&b.ngram))
+    });
+    ngrams.truncate(count);
+    ngrams
+}
+
+/// Typing done from one starting position in the book.
+struct Run<'a> {
+    started: DateTime<Utc>,
+    completed: DateTime<Utc>,
+    start_index: usize,
+    end_index: usize,
+    /// Whether the keypress which ended the run was logged after it completed,
+    /// as it is for samples and drills.
+    ended_by_next_key: bool,
+    pauses: &'a [Pause],
+}
+
+/// Where in the book a keypress was typed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Placement<'a> {
+    /// Index of the character the keypress was meant to type.
+    pub index: usize,
+    /// Range of the sample, drill or timed test it was typed in.
+    pub start_index: usize,
+    pub end_index: usize,
+    /// When that sample, drill or timed test started.
+    pub started: DateTime<Utc>,
+    /// Breaks taken during it.
+    pub pauses: &'a [Pause],
+}
+
+/// Finds where in the book each keypress was typed, by replaying the
+/// keypresses against the samples, drills and timed tests they belong to.
+/// Keypresses which don't belong to a logged test have no placement.
+pub fn keypress_placements<'a>(
+    keypresses: &[KeyPress],
+    tests: &'a [Test],
+    drills: &'a [Test],
+    timed_tests: &'a [TimedTest],
+) -> Vec<Option<Placement<'a>>> {
+    let mut runs = tests
+        .iter()
+        .chain(drills)
+        .map(|t| Run {
+            started: t.started,
+            completed: t.completed,
+            start_index: t.start_index,
+            end_index: t.end_index,
+            ended_by_next_key: true,
+            pauses: &t.pauses,
+        })
+        .chain(timed_tests.iter().map(|t| Run {
+            started: t.started,
+            completed: t.completed,
+            start_index: t.start_index,
+            end_index: t.end_index,
+            ended_by_next_key: false,
+            pauses: &t.pauses,
+        }))
+        .collect::<Vec<_>>();
+    runs.sort_by_key(|r| r.started);
+
+    let mut owners = keypresses
+        .iter()
+        .map(|k| {
+            let r = runs.partition_point(|r| r.started <= k.time).checked_sub(1)?;
+            let run = &runs[r];
+            let within = if run.ended_by_next_key {
+                k.time < run.completed
+            } else {
+                k.time <= run.completed
+            };
+            within.then_some(r)
+        })
+        .collect::<Vec<_>>();
+    for (r, run) in runs.iter().enumerate() {
+        if run.ended_by_next_key {
+            let i = keypresses.partition_point(|k| k.time < run.completed);
+            if let Some(owner) = owners.get_mut(i) {
+                *owner = Some(r);
+            }
+        }
+    }
+
+    let mut cursors = runs.iter().map(|r| r.start_index).collect::<Vec<_>>();
+    keypresses
+        .iter()
+        .zip(owners)
+        .map(|(k, owner)| {
+            let run = &runs[owner?];
+            let index = cursors[owner?];
+            if k.correct {
+                cursors[owner?] += 1;
+            }
+            (index < run.end_index || (index == run.end_index && !k.correct)).then_some(Placement {
+                index,
+                start_index: run.start_index,
+                end_index: run.end_index,
+                started: run.started,
+                pauses: run.pauses,
+            })
+        })
+        .collect()
+}
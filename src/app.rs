@@ -1,60 +1,317 @@
-use chrono::{serde::ts_nanoseconds, DateTime, Utc};
+use crate::analysis::{self, KeyStats, NgramStats};
+use crate::clock::Clock;
+use crate::config::Config;
+use crate::drill::{self, Targets};
+use crate::ghost::{self, Attempt, Ghosts};
+use crate::goals::Activity;
+use crate::inspect::Inspection;
+use crate::search::Search;
+use crate::log::{self, Bookmark, KeyPress, Logs, Pause, Position, Session, Test, TimedTest};
+use crate::profile;
+use crate::progress::{self, Progress};
+use crate::replay::{self, Replay};
+use crate::keymap::Keymap;
+use crate::theme::Theme;
+use chrono::{DateTime, Duration, Utc};
 use deunicode::deunicode;
+use ratatui::layout::Rect;
 use regex::Regex;
-use serde::{Deserialize, Serialize};
-use std::{error, fs, fs::File, io::Read, io::Seek, io::Write};
+use std::{collections::HashMap, error, fs, fs::File, io::Seek, io::Write};
 
 pub const DEFAULT_TEXT_WIDTH_PERCENT: u16 = 60;
 pub const FULL_TEXT_WIDTH_PERCENT: u16 = 95;
 const STARTING_SAMPLE_SIZE: usize = 100;
+/// Width of the charts panel when it is beside the text.
+pub const CHARTS_WIDTH: u16 = 40;
+/// Height of the charts panel when it is below the text.
+pub const CHARTS_HEIGHT: u16 = 12;
+/// Narrowest terminal which fits the charts panel beside the text.
+const SIDE_CHARTS_MIN_WIDTH: u16 = 120;
+/// Number of n-grams listed in the transitions panel.
+const SLOWEST_NGRAMS: usize = 20;
+/// Number of most missed keys in a session summary.
+const SESSION_MOST_MISSED: usize = 5;
 
 /// Application result type.
 pub type AppResult<T> = std::result::Result<T, Box<dyn error::Error>>;
 
+/// Practice mode.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    /// Adaptive samples which end on the first mistake.
+    Samples,
+    /// Continuous typing from the current position until the clock runs out.
+    Timed(Duration),
+    /// Passages dense in weak keys and bigrams, which don't move the book position.
+    Drill,
+    /// Playback of recorded keypresses, which doesn't take any typing.
+    Replay(replay::Window),
+}
+
+/// Panel drawn over the text.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Overlay {
+    /// Keyboard colored by typing statistics.
+    Heatmap(HeatmapView),
+    /// Tables of the slowest bigrams and trigrams.
+    Transitions,
+    /// Key bindings and an explanation of the samples and colors.
+    Help,
+    /// Past outcomes of a clicked word.
+    Inspect,
+    /// Bookmarks of the book, to go to one of them.
+    Bookmarks,
+}
+
+/// Statistic the keys of the heatmap are colored by.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HeatmapView {
+    /// How often the key was missed when it was expected.
+    Missed,
+    /// How often the key was typed when another was expected.
+    Mistyped,
+    /// Mean time taken to type the key.
+    Latency,
+}
+
+impl HeatmapView {
+    pub fn next(self) -> Self {
+        match self {
+            HeatmapView::Missed => HeatmapView::Mistyped,
+            HeatmapView::Mistyped => HeatmapView::Latency,
+            HeatmapView::Latency => HeatmapView::Missed,
+        }
+    }
+}
+
 /// Application.
 #[derive(Debug)]
 pub struct App {
     /// Is the application running?
     pub running: bool,
+    /// Where the time is taken from.
+    pub clock: Clock,
+    /// Logs of the profile practicing.
+    pub logs: Logs,
+    book_title: String,
+    pub config: Config,
+    pub theme: Theme,
+    pub keymap: Keymap,
+    /// Practice across all books before this session.
+    activity: Activity,
     book_text: String,
     keypress_log: File,
     test_log: File,
+    timed_log: File,
+    drill_log: File,
+    position_log: File,
+    bookmark_log: File,
+    /// Samples of the book, which don't include drills.
+    pub tests: Vec<Test>,
+    /// Drills, which are logged apart from the book's samples.
+    pub drills: Vec<Test>,
+    timed_tests: Vec<TimedTest>,
+    /// Places chosen to practice from, in the order they were chosen.
+    positions: Vec<Position>,
+    pub bookmarks: Vec<Bookmark>,
+    /// Index into `bookmarks` of the one selected in the bookmarks panel.
+    pub selected_bookmark: usize,
+    /// Name of a new bookmark, while it is being typed into its prompt.
+    pub bookmark_name: Option<String>,
+    /// Index last scrolled to by a search or a bookmark.
+    jumped_to: Option<usize>,
+    /// Chapter headings and the indices they start at.
+    chapters: Vec<(usize, String)>,
+    pub progress: Progress,
+    pub mode: Mode,
     pub book_lines: Vec<String>,
     pub line_index: Vec<(usize, usize)>,
     pub sample_start_index: usize,
     pub sample_len: usize,
-    start_time: DateTime<Utc>,
+    /// Breaks taken during the current sample or timed test.
+    pauses: Vec<Pause>,
+    /// When the clock was paused, while it is.
+    pub paused_since: Option<DateTime<Utc>>,
+    /// Time of the last keypress, or of the start of the sample.
+    last_activity: DateTime<Utc>,
     pub cur_char: usize,
     pub following_typing: bool,
     pub display_line: usize,
     pub text_width_percent: u16,
     pub terminal_width: u16,
     pub full_text_width: bool,
+    pub show_charts: bool,
+    /// When the clock of a timed test started, set by the first keypress.
+    timed_start: Option<DateTime<Utc>>,
+    keypresses: usize,
+    mistakes: usize,
+    pub timed_result: Option<TimedTest>,
+    pub overlay: Option<Overlay>,
+    /// Number of lines the help is scrolled down by.
+    pub help_scroll: u16,
+    /// Word shown by the inspect overlay.
+    pub inspection: Option<Inspection>,
+    /// Search through the book, while its prompt is open or its matches are
+    /// highlighted.
+    pub search: Option<Search>,
+    /// Where the text was last drawn, and the line drawn at its top.
+    pub text_area: Rect,
+    pub first_drawn_line: usize,
+    /// Statistics for each key, loaded when the heatmap is opened.
+    pub key_stats: HashMap<char, KeyStats>,
+    /// Slowest bigrams and trigrams, loaded when the transitions panel is opened.
+    pub slowest_bigrams: Vec<NgramStats>,
+    pub slowest_trigrams: Vec<NgramStats>,
+    pub drill_targets: Targets,
+    /// Passages of a drill, as `(start_index, len)` pairs.
+    pub drill_passages: Vec<(usize, usize)>,
+    pub drill_index: usize,
+    pub replay: Option<Replay>,
+    /// Best earlier attempts, raced when a sample starts from the same position.
+    ghosts: Ghosts,
+    /// Ghost raced in the current sample.
+    pub ghost: Option<Attempt>,
+    /// Typing of the current sample, timed from its first keypress.
+    attempt: Attempt,
+    attempt_started: Option<DateTime<Utc>>,
+    /// Result of the last race against a ghost.
+    pub ghost_outcome: Option<ghost::Outcome>,
+    /// When the last sample failed, while its mistake is shown.
+    mistake_shown: Option<DateTime<Utc>>,
+    session_started: DateTime<Utc>,
+    /// Characters advanced this session before the last jump to another
+    /// place, and the position in the book since that jump, or since the
+    /// session started.
+    session_chars_advanced: usize,
+    segment_start_position: usize,
+    /// Lengths of the logs when the session started.
+    session_first_test: usize,
+    session_first_drill: usize,
+    session_first_timed_test: usize,
+    session_keypresses: usize,
+    session_mistakes: usize,
+    /// Times each expected character was missed this session.
+    session_misses: HashMap<char, usize>,
+    /// Summary shown once the session has ended.
+    pub summary: Option<Session>,
+    /// Profiles to switch between, while the picker is open.
+    pub profile_picker: Option<profile::Picker>,
 }
 
 impl App {
     /// Constructs a new instance of [`App`].
-    pub fn new(book_title: &str, terminal_width: u16) -> AppResult<Self> {
-        
-        let book_text = App::load_book(book_title)?;
-
-        let _ = fs::create_dir(
-            dirs::home_dir()
-                .unwrap()
-                .join(".booktyping")
-                .join(book_title),
-        );
+    pub fn new(
+        logs: Logs,
+        book_title: &str,
+        terminal_width: u16,
+        mode: Mode,
+        config: Config,
+    ) -> AppResult<Self> {
+        App::with_clock(logs, book_title, terminal_width, mode, config, Clock::System)
+    }
 
-        let mut test_log = App::get_test_log(book_title)?;
+    /// Constructs an [`App`] which takes the time from `clock`.
+    pub fn with_clock(
+        logs: Logs,
+        book_title: &str,
+        terminal_width: u16,
+        mode: Mode,
+        config: Config,
+        clock: Clock,
+    ) -> AppResult<Self> {
+        let book_text = App::load_book(&logs, book_title)?;
 
-        let (sample_start_index, sample_len) = App::get_next_sample(&mut test_log, &book_text)?;
+        let _ = fs::create_dir_all(logs.book_dir(book_title));
 
+        let mut test_log = logs.open_json_log(book_title, "tests.json")?;
+        let mut timed_log = logs.open_json_log(book_title, "timed.json")?;
+        let mut drill_log = logs.open_json_log(book_title, "drills.json")?;
+        let mut position_log = logs.open_json_log(book_title, "positions.json")?;
+        let mut bookmark_log = logs.open_json_log(book_title, "bookmarks.json")?;
+
+        let tests: Vec<Test> = log::read_log(&mut test_log)?;
+        let drills: Vec<Test> = log::read_log(&mut drill_log)?;
+        let timed_tests: Vec<TimedTest> = log::read_log(&mut timed_log)?;
+        let positions: Vec<Position> = log::read_log(&mut position_log)?;
+        let bookmarks: Vec<Bookmark> = log::read_log(&mut bookmark_log)?;
+        let chapters = progress::find_chapters(&book_text);
+        let progress = Progress::new(&book_text, &chapters, &tests, &timed_tests, &positions);
+
+        let mut drill_targets = Targets::default();
+        let mut drill_passages = Vec::new();
+        let mut replay = None;
+        let (sample_start_index, sample_len) = match mode {
+            Mode::Samples => App::get_next_sample(&tests, &timed_tests, &positions, &book_text),
+            Mode::Timed(_) => {
+                let (start_index, _) =
+                    App::get_next_sample(&tests, &timed_tests, &positions, &book_text);
+                (start_index, book_text.len() - start_index - 1)
+            }
+            Mode::Drill => {
+                drill_targets = Targets::from_keypresses(&logs.read_keypresses(book_title)?);
+                if drill_targets.is_empty() {
+                    return Err("Not enough keypresses recorded yet to find weak keys to drill".into());
+                }
+                drill_passages = drill::find_passages(&book_text, &drill_targets, drill::PASSAGES);
+                *drill_passages
+                    .first()
+                    .ok_or("No passages in the book contain the keys to drill")?
+            }
+            Mode::Replay(window) => {
+                let loaded = Replay::load(&logs, book_title, window, clock.now())?;
+                let frame = loaded.frame();
+                let sample = (frame.sample_start_index, frame.sample_len);
+                replay = Some(loaded);
+                sample
+            }
+        };
+
+        let ghosts = match mode {
+            Mode::Samples | Mode::Drill => Ghosts::from_logs(
+                &logs.read_keypresses(book_title)?,
+                &tests,
+                &drills,
+            ),
+            _ => Ghosts::default(),
+        };
+        let ghost = ghosts.get(sample_start_index).cloned();
+
+        let segment_start_position = progress.position;
+        let session_first_test = tests.len();
+        let session_first_drill = drills.len();
+        let session_first_timed_test = timed_tests.len();
+
+        let now = clock.now();
         let mut ret = Self {
             running: true,
-            keypress_log: App::get_keypress_log(book_title)?,
-            start_time: Utc::now(), 
+            clock,
+            book_title: book_title.to_owned(),
+            theme: Theme::from_config(&config)?,
+            keymap: Keymap::from_config(&config)?,
+            config,
+            activity: Activity::load(&logs)?,
+            keypress_log: logs.open_keypress_log(book_title)?,
+            logs,
+            pauses: Vec::new(),
+            paused_since: None,
+            last_activity: now,
             cur_char: 0,
             test_log,
+            timed_log,
+            drill_log,
+            position_log,
+            bookmark_log,
+            tests,
+            drills,
+            timed_tests,
+            positions,
+            bookmarks,
+            selected_bookmark: 0,
+            bookmark_name: None,
+            jumped_to: None,
+            chapters,
+            progress,
+            mode,
             book_text,
             sample_start_index,
             sample_len,
@@ -62,13 +319,48 @@ impl App {
             following_typing: true,
             text_width_percent: DEFAULT_TEXT_WIDTH_PERCENT,
             full_text_width: false,
+            show_charts: false,
             book_lines: Default::default(),
             line_index: Default::default(),
             display_line: Default::default(),
+            timed_start: None,
+            keypresses: 0,
+            mistakes: 0,
+            timed_result: None,
+            overlay: None,
+            help_scroll: 0,
+            inspection: None,
+            search: None,
+            text_area: Rect::default(),
+            first_drawn_line: 0,
+            key_stats: Default::default(),
+            slowest_bigrams: Default::default(),
+            slowest_trigrams: Default::default(),
+            drill_targets,
+            drill_passages,
+            drill_index: 0,
+            replay,
+            ghosts,
+            ghost,
+            attempt: Attempt::default(),
+            attempt_started: None,
+            ghost_outcome: None,
+            mistake_shown: None,
+            session_started: now,
+            session_chars_advanced: 0,
+            segment_start_position,
+            session_first_test,
+            session_first_drill,
+            session_first_timed_test,
+            session_keypresses: 0,
+            session_mistakes: 0,
+            session_misses: Default::default(),
+            summary: None,
+            profile_picker: None,
         };
 
         ret.generate_lines();
-        
+
         Ok(ret)
     }
 
@@ -77,84 +369,670 @@ impl App {
         self.running = false;
     }
 
+    /// Practice across all books, including this session.
+    pub fn activity(&self) -> Activity {
+        let mut activity = self.activity.clone();
+        for t in &self.tests[self.session_first_test..] {
+            activity.add_test(t, true);
+        }
+        for t in &self.drills[self.session_first_drill..] {
+            activity.add_test(t, false);
+        }
+        for t in &self.timed_tests[self.session_first_timed_test..] {
+            activity.add_timed_test(t);
+        }
+        activity
+    }
+
+    /// Saves a summary of the session and shows it, or quits straight away
+    /// if nothing was typed.
+    pub fn end_session(&mut self) -> AppResult<()> {
+        match self.save_session()? {
+            Some(session) => {
+                self.overlay = None;
+                self.summary = Some(session);
+            }
+            None => self.quit(),
+        }
+        Ok(())
+    }
+
+    /// Adds a summary of the session to the log, if anything was typed.
+    fn save_session(&mut self) -> AppResult<Option<Session>> {
+        self.end_timed()?;
+        if self.session_keypresses == 0 {
+            return Ok(None);
+        }
+        let tests = self.tests[self.session_first_test..]
+            .iter()
+            .chain(&self.drills[self.session_first_drill..])
+            .collect::<Vec<_>>();
+        let timed_tests = &self.timed_tests[self.session_first_timed_test..];
+        let wpm = tests
+            .iter()
+            .filter_map(|t| t.wpm)
+            .chain(timed_tests.iter().map(|t| t.net_wpm))
+            .collect::<Vec<_>>();
+        let mut most_missed = self
+            .session_misses
+            .iter()
+            .map(|(&c, &count)| (c, count))
+            .collect::<Vec<_>>();
+        most_missed.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        most_missed.truncate(SESSION_MOST_MISSED);
+
+        let session = Session {
+            started: self.session_started,
+            ended: self.clock.now(),
+            samples: tests.len(),
+            samples_passed: tests.iter().filter(|t| t.succeeded).count(),
+            chars_advanced: self.session_chars_advanced
+                + self
+                    .progress
+                    .position
+                    .saturating_sub(self.segment_start_position),
+            practice_seconds: tests.iter().map(|t| t.duration().num_seconds()).sum::<i64>()
+                + timed_tests
+                    .iter()
+                    .map(|t| t.duration().num_seconds())
+                    .sum::<i64>(),
+            average_wpm: (!wpm.is_empty()).then(|| wpm.iter().sum::<f64>() / wpm.len() as f64),
+            best_wpm: wpm.iter().copied().reduce(f64::max),
+            accuracy: Some(
+                (self.session_keypresses - self.session_mistakes) as f64
+                    / self.session_keypresses as f64,
+            ),
+            most_missed,
+        };
+
+        let mut session_log = self.logs.open_json_log(&self.book_title, "sessions.json")?;
+        let mut sessions: Vec<Session> = log::read_log(&mut session_log)?;
+        sessions.push(session.clone());
+        session_log.seek(std::io::SeekFrom::Start(0))?;
+        session_log.write_all(&serde_json::to_vec(&sessions)?)?;
+        Ok(Some(session))
+    }
+
+    /// Opens the profile picker, or closes it.
+    pub fn toggle_profiles(&mut self) -> AppResult<()> {
+        self.profile_picker = match self.profile_picker {
+            Some(_) => None,
+            None => Some(profile::Picker::load(&self.logs, &self.book_title)?),
+        };
+        Ok(())
+    }
+
+    /// Ends the session and starts the book again with the logs and config
+    /// of another profile. If the book can't be started for that profile,
+    /// the current one is kept and the picker shows why.
+    pub fn switch_profile(&mut self, profile: Option<String>) -> AppResult<()> {
+        let logs = self.logs.with_profile(profile);
+        let app = Config::load(&logs).and_then(|config| {
+            App::with_clock(
+                logs,
+                &self.book_title,
+                self.terminal_width,
+                self.mode,
+                config,
+                self.clock.clone(),
+            )
+        });
+        match (app, &mut self.profile_picker) {
+            (Ok(app), _) => {
+                self.save_session()?;
+                *self = app;
+            }
+            (Err(e), Some(picker)) => picker.error = Some(e.to_string()),
+            (Err(e), None) => return Err(e),
+        }
+        Ok(())
+    }
+
+    pub fn toggle_charts(&mut self) {
+        self.show_charts = !self.show_charts;
+        self.generate_lines();
+    }
+
+    /// Whether the charts panel is beside the text rather than below it.
+    pub fn charts_beside_text(&self) -> bool {
+        self.terminal_width >= SIDE_CHARTS_MIN_WIDTH
+    }
+
+    /// Width of the area the text is drawn in.
+    pub fn text_area_width(&self) -> u16 {
+        if self.show_charts && self.charts_beside_text() {
+            self.terminal_width - CHARTS_WIDTH
+        } else {
+            self.terminal_width
+        }
+    }
+
+    /// Opens the key heatmap, or closes it if it is open.
+    pub fn toggle_heatmap(&mut self) -> AppResult<()> {
+        if let Some(Overlay::Heatmap(_)) = self.overlay {
+            self.overlay = None;
+        } else {
+            self.key_stats = analysis::key_stats(&self.logs.read_keypresses(&self.book_title)?);
+            self.overlay = Some(Overlay::Heatmap(HeatmapView::Missed));
+        }
+        Ok(())
+    }
+
+    /// Opens the help, or closes it if it is open.
+    pub fn toggle_help(&mut self) {
+        if let Some(Overlay::Help) = self.overlay {
+            self.overlay = None;
+        } else {
+            self.help_scroll = 0;
+            self.overlay = Some(Overlay::Help);
+        }
+    }
+
+    /// Index of the character drawn at a position on the screen.
+    pub fn index_at(&self, column: u16, row: u16) -> Option<usize> {
+        let area = self.text_area;
+        if column < area.x || column >= area.right() || row < area.y || row >= area.bottom() {
+            return None;
+        }
+        let position = (
+            self.first_drawn_line + (row - area.y) as usize,
+            (column - area.x) as usize,
+        );
+        let index = self.line_index.partition_point(|&p| p < position);
+        (self.line_index.get(index) == Some(&position)).then_some(index)
+    }
+
+    /// Opens the past outcomes of the word at an index, returning false if
+    /// there is no word there.
+    pub fn inspect(&mut self, index: usize) -> bool {
+        self.inspection = Inspection::new(&self.book_text, index, &self.tests, &self.timed_tests);
+        self.overlay = self.inspection.as_ref().map(|_| Overlay::Inspect);
+        self.inspection.is_some()
+    }
+
+    /// Finds the matches of the search query again, selecting the first one
+    /// from the top of the text shown, and scrolls to it.
+    pub fn update_search(&mut self) {
+        let from = self
+            .line_index
+            .partition_point(|&(line, _)| line < self.first_drawn_line);
+        if let Some(search) = &mut self.search {
+            search.find(&self.book_text, from);
+        }
+        self.show_search_match();
+    }
+
+    /// Scrolls to the selected match of the search.
+    pub fn show_search_match(&mut self) {
+        let Some(index) = self.search.as_ref().and_then(Search::current_match) else {
+            return;
+        };
+        self.jump_to(index);
+    }
+
+    /// Scrolls the text to an index, or to the end for an index past it,
+    /// as a bookmark can be once the book has been edited.
+    fn jump_to(&mut self, index: usize) {
+        let index = usize::min(index, self.line_index.len().saturating_sub(1));
+        self.following_typing = false;
+        self.display_line = self.line_index[index].0;
+        self.jumped_to = Some(index);
+    }
+
+    /// Start of the word at the place scrolled to: the match of a search or
+    /// bookmark last gone to while its line is shown, or else the start of
+    /// the line shown in the middle. Returns `None` while following the
+    /// typing.
+    pub fn scrolled_index(&self) -> Option<usize> {
+        if self.following_typing {
+            return None;
+        }
+        let index = match self.jumped_to {
+            Some(index) if self.line_index[index].0 == self.display_line => index,
+            _ => self
+                .line_index
+                .partition_point(|&(line, _)| line < self.display_line),
+        };
+        let index = usize::min(index, self.book_text.len() - 1);
+        Some(
+            self.book_text.as_bytes()[..index]
+                .iter()
+                .rposition(|&b| b == b' ')
+                .map_or(0, |i| i + 1),
+        )
+    }
+
+    /// Starts practicing from an index, recording it as the position in the
+    /// book. The sample in progress is dropped without being logged.
+    pub fn start_practicing(&mut self, index: usize) -> AppResult<()> {
+        self.positions.push(Position {
+            index,
+            chosen: self.clock.now(),
+        });
+        self.position_log.seek(std::io::SeekFrom::Start(0))?;
+        self.position_log
+            .write_all(&serde_json::to_vec(&self.positions)?)?;
+        // Don't count the jump as progress made this session.
+        self.session_chars_advanced += self
+            .progress
+            .position
+            .saturating_sub(self.segment_start_position);
+        self.update_progress();
+        self.segment_start_position = self.progress.position;
+
+        (self.sample_start_index, self.sample_len) = self.next_sample(false)?;
+        self.cur_char = 0;
+        self.keypresses = 0;
+        self.mistakes = 0;
+        self.pauses.clear();
+        self.paused_since = None;
+        self.last_activity = self.clock.now();
+        self.attempt = Attempt::default();
+        self.attempt_started = None;
+        self.ghost = self.ghosts.get(self.sample_start_index).cloned();
+        self.ghost_outcome = None;
+        self.following_typing = true;
+        self.search = None;
+        self.jumped_to = None;
+        Ok(())
+    }
+
+    /// Adds a bookmark at the place scrolled to, or at the cursor.
+    pub fn add_bookmark(&mut self, name: String) -> AppResult<()> {
+        let index = self
+            .scrolled_index()
+            .unwrap_or(self.sample_start_index + self.cur_char);
+        self.bookmarks.push(Bookmark {
+            name,
+            index,
+            created: self.clock.now(),
+        });
+        self.write_bookmarks()
+    }
+
+    /// Deletes the bookmark selected in the bookmarks panel.
+    pub fn delete_bookmark(&mut self) -> AppResult<()> {
+        if self.selected_bookmark >= self.bookmarks.len() {
+            return Ok(());
+        }
+        self.bookmarks.remove(self.selected_bookmark);
+        self.selected_bookmark = self
+            .selected_bookmark
+            .min(self.bookmarks.len().saturating_sub(1));
+        self.write_bookmarks()
+    }
+
+    fn write_bookmarks(&mut self) -> AppResult<()> {
+        // The log can get shorter, unlike the others.
+        self.bookmark_log.set_len(0)?;
+        self.bookmark_log.seek(std::io::SeekFrom::Start(0))?;
+        self.bookmark_log
+            .write_all(&serde_json::to_vec(&self.bookmarks)?)?;
+        Ok(())
+    }
+
+    /// Scrolls to the bookmark selected in the bookmarks panel and closes it.
+    pub fn go_to_bookmark(&mut self) {
+        if let Some(bookmark) = self.bookmarks.get(self.selected_bookmark) {
+            self.jump_to(bookmark.index);
+            self.overlay = None;
+        }
+    }
+
+    /// Opens the bookmarks panel, or closes it if it is open.
+    pub fn toggle_bookmarks(&mut self) {
+        if let Some(Overlay::Bookmarks) = self.overlay {
+            self.overlay = None;
+        } else {
+            self.selected_bookmark = self
+                .selected_bookmark
+                .min(self.bookmarks.len().saturating_sub(1));
+            self.overlay = Some(Overlay::Bookmarks);
+        }
+    }
+
+    /// Chapter an index of the book is in.
+    pub fn chapter_at(&self, index: usize) -> Option<&str> {
+        self.chapters
+            .iter()
+            .take_while(|&&(start, _)| start <= index)
+            .last()
+            .map(|(_, chapter)| chapter.as_str())
+    }
+
+    /// Length of the book text.
+    pub fn book_len(&self) -> usize {
+        self.book_text.len()
+    }
+
+    /// Opens the transitions panel, or closes it if it is open.
+    pub fn toggle_transitions(&mut self) -> AppResult<()> {
+        if let Some(Overlay::Transitions) = self.overlay {
+            self.overlay = None;
+        } else {
+            let keypresses = self.logs.read_keypresses(&self.book_title)?;
+            self.slowest_bigrams = analysis::slowest_ngrams(&keypresses, 2, SLOWEST_NGRAMS);
+            self.slowest_trigrams = analysis::slowest_ngrams(&keypresses, 3, SLOWEST_NGRAMS);
+            self.overlay = Some(Overlay::Transitions);
+        }
+        Ok(())
+    }
+
     pub fn handle_char(&mut self, c: char) -> AppResult<()> {
+        let now = self.clock.now();
+        self.resume(now);
+        self.last_activity = now;
+        self.mistake_shown = None;
+        if let Mode::Timed(_) = self.mode {
+            return self.handle_timed_char(c);
+        }
         if !self.following_typing {
             self.following_typing = true;
         }
-        let correct = c
-            == self
-                .book_text
-                .chars()
-                .nth(self.sample_start_index + self.cur_char)
-                .unwrap();
+        let expected = self
+            .book_text
+            .chars()
+            .nth(self.sample_start_index + self.cur_char)
+            .unwrap();
+        let correct = c == expected;
 
+        let attempt_started = *self.attempt_started.get_or_insert(now);
+        self.keypresses += 1;
         if correct {
-            self.cur_char += 1
+            self.cur_char += 1;
+            self.attempt
+                .times
+                .push(self.active_time(attempt_started, now));
+        } else {
+            self.mistakes += 1;
         }
         if !correct || self.cur_char == self.sample_len {
-            self.log_test(correct)?;
-            self.start_time = Utc::now();
-            (self.sample_start_index, self.sample_len) =
-                App::get_next_sample(&mut self.test_log, &self.book_text)?;
+            self.log_test((!correct).then_some(c))?;
+            if !correct && self.config.mistake_millis > 0 {
+                self.mistake_shown = Some(now);
+            }
+            self.finish_attempt(correct);
+            self.last_activity = self.clock.now();
+            (self.sample_start_index, self.sample_len) = self.next_sample(correct)?;
 
             self.cur_char = 0;
+            self.keypresses = 0;
+            self.mistakes = 0;
+            self.ghost = self.ghosts.get(self.sample_start_index).cloned();
+        }
+
+        self.log_keypress(correct, c, expected)
+    }
+
+    /// Compares the sample which has ended with its ghost, and keeps it as
+    /// the ghost for its starting position if it did better.
+    fn finish_attempt(&mut self, succeeded: bool) {
+        let attempt = std::mem::take(&mut self.attempt);
+        self.attempt_started = None;
+        self.ghost_outcome = self
+            .ghost
+            .as_ref()
+            .map(|ghost| ghost::Outcome::new(&attempt, ghost, succeeded));
+        self.ghosts.add(self.sample_start_index, attempt);
+    }
+
+    /// Index where the last sample failed and the character typed there,
+    /// while it is shown.
+    pub fn shown_mistake(&self) -> Option<(usize, char)> {
+        self.mistake_shown?;
+        let test = match self.mode {
+            Mode::Drill => self.drills.last()?,
+            _ => self.tests.last()?,
+        };
+        Some((test.end_index, test.mistyped?))
+    }
+
+    /// Index of the character the ghost is on, once the sample has started.
+    pub fn ghost_index(&self) -> Option<usize> {
+        let ghost = self.ghost.as_ref()?;
+        let elapsed = self.active_time(self.attempt_started?, self.clock.now());
+        Some(self.sample_start_index + usize::min(ghost.position(elapsed), self.sample_len))
+    }
+
+    /// Time spent typing the current sample or timed test, leaving out
+    /// pauses, and the speed of the typing once there is enough to measure.
+    pub fn live_speed(&self) -> (Duration, Option<f64>) {
+        let started = match self.mode {
+            Mode::Timed(_) => self.timed_start,
+            _ => self.attempt_started,
+        };
+        let Some(started) = started else {
+            return (Duration::zero(), None);
+        };
+        let elapsed = match &self.timed_result {
+            Some(result) => result.duration(),
+            None => self.active_time(started, self.clock.now()),
+        };
+        let minutes = elapsed.num_milliseconds() as f64 / 60_000.0;
+        let wpm = (elapsed >= Duration::seconds(1)).then(|| self.cur_char as f64 / 5.0 / minutes);
+        (elapsed, wpm)
+    }
+
+    /// Whether there is a clock running which can be paused.
+    fn can_pause(&self) -> bool {
+        self.paused_since.is_none()
+            && self.replay.is_none()
+            && self.summary.is_none()
+            && match self.mode {
+                Mode::Timed(_) => self.timed_start.is_some() && self.timed_result.is_none(),
+                _ => true,
+            }
+    }
+
+    /// Pauses the clock, or resumes it if it is paused.
+    pub fn toggle_pause(&mut self) {
+        let now = self.clock.now();
+        if self.paused_since.is_some() {
+            self.resume(now);
+            self.last_activity = now;
+        } else if self.can_pause() {
+            self.paused_since = Some(now);
+        }
+    }
+
+    /// Ends the pause, if the clock is paused, keeping it to be logged with
+    /// the sample or timed test.
+    fn resume(&mut self, now: DateTime<Utc>) {
+        if let Some(started) = self.paused_since.take() {
+            self.pauses.push(Pause {
+                started,
+                ended: now,
+            });
+        }
+    }
+
+    /// Time between `from` and `to` which wasn't spent paused.
+    fn active_time(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Duration {
+        let mut paused = log::paused_time(&self.pauses, from, to);
+        if let Some(since) = self.paused_since {
+            paused += Duration::max(to - DateTime::max(since, from), Duration::zero());
+        }
+        to - from - paused
+    }
+
+    /// Handles a typed character in timed mode, where mistakes are counted
+    /// but don't end the sample.
+    fn handle_timed_char(&mut self, c: char) -> AppResult<()> {
+        self.tick()?;
+        if self.timed_result.is_some() {
+            return Ok(());
+        }
+        if !self.following_typing {
+            self.following_typing = true;
+        }
+        if self.timed_start.is_none() {
+            self.timed_start = Some(self.clock.now());
+        }
+        let expected = self
+            .book_text
+            .chars()
+            .nth(self.sample_start_index + self.cur_char)
+            .unwrap();
+        let correct = c == expected;
+
+        self.keypresses += 1;
+        if correct {
+            self.cur_char += 1;
+        } else {
+            self.mistakes += 1;
+        }
+        self.log_keypress(correct, c, expected)?;
+
+        if self.cur_char == self.sample_len {
+            self.finish_timed(self.clock.now())?;
+        }
+        Ok(())
+    }
+
+    /// Handles the passage of time, ending a timed test once its clock runs out.
+    pub fn tick(&mut self) -> AppResult<()> {
+        let now = self.clock.now();
+        if self.config.idle_seconds > 0
+            && self.can_pause()
+            && now - self.last_activity >= Duration::seconds(self.config.idle_seconds as i64)
+        {
+            // The whole time since the last keypress was a break.
+            self.paused_since = Some(self.last_activity);
+        }
+        if let (Mode::Timed(duration), Some(start), None) =
+            (self.mode, self.timed_start, &self.timed_result)
+        {
+            let elapsed = self.active_time(start, now);
+            if elapsed >= duration {
+                self.finish_timed(now - (elapsed - duration))?;
+            }
+        }
+        let shown_for = Duration::milliseconds(self.config.mistake_millis as i64);
+        if self.mistake_shown.is_some_and(|shown| now - shown >= shown_for) {
+            self.mistake_shown = None;
         }
+        if let Some(replay) = &mut self.replay {
+            replay.update(self.clock.now());
+        }
+        self.show_replay_frame();
+        Ok(())
+    }
+
+    /// Moves the text to the state of the replay.
+    pub fn show_replay_frame(&mut self) {
+        if let Some(replay) = &self.replay {
+            let frame = replay.frame();
+            self.sample_start_index = frame.sample_start_index;
+            self.sample_len = frame.sample_len;
+            self.cur_char = frame.cur_char;
+        }
+    }
+
+    /// Time left in a timed test, if one is running.
+    pub fn time_remaining(&self) -> Option<Duration> {
+        let Mode::Timed(duration) = self.mode else {
+            return None;
+        };
+        if self.timed_result.is_some() {
+            return Some(Duration::zero());
+        }
+        Some(match self.timed_start {
+            Some(start) => Duration::max(
+                duration - self.active_time(start, self.clock.now()),
+                Duration::zero(),
+            ),
+            None => duration,
+        })
+    }
+
+    /// Logs a timed test which is still running as far as it got, so that
+    /// it isn't lost when the session ends before its time is up.
+    fn end_timed(&mut self) -> AppResult<()> {
+        if let (Mode::Timed(_), Some(_), None) = (self.mode, self.timed_start, &self.timed_result)
+        {
+            // The time may have run out, or the keyboard been left idle, since
+            // the last tick.
+            self.tick()?;
+            if self.timed_result.is_none() {
+                self.finish_timed(self.clock.now())?;
+            }
+        }
+        Ok(())
+    }
+
+    fn finish_timed(&mut self, completed: DateTime<Utc>) -> AppResult<()> {
+        let started = self.timed_start.unwrap_or(completed);
+        self.resume(completed);
+        let minutes = self.active_time(started, completed).num_milliseconds() as f64 / 60_000.0;
+        let (gross_wpm, net_wpm, accuracy) = if minutes > 0.0 && self.keypresses > 0 {
+            let gross = self.keypresses as f64 / 5.0 / minutes;
+            let net = f64::max(gross - self.mistakes as f64 / minutes, 0.0);
+            let accuracy = (self.keypresses - self.mistakes) as f64 / self.keypresses as f64;
+            (gross, net, accuracy)
+        } else {
+            (0.0, 0.0, 0.0)
+        };
+        let result = TimedTest {
+            start_index: self.sample_start_index,
+            end_index: self.sample_start_index + self.cur_char,
+            keypresses: self.keypresses,
+            mistakes: self.mistakes,
+            gross_wpm,
+            net_wpm,
+            accuracy,
+            started,
+            completed,
+            pauses: std::mem::take(&mut self.pauses),
+        };
+
+        self.timed_tests.push(result.clone());
+        self.timed_log.seek(std::io::SeekFrom::Start(0))?;
+        self.timed_log
+            .write_all(&serde_json::to_vec(&self.timed_tests)?)?;
+        self.update_progress();
 
+        self.timed_result = Some(result);
+        Ok(())
+    }
+
+    fn log_keypress(&mut self, correct: bool, c: char, expected: char) -> AppResult<()> {
+        self.session_keypresses += 1;
+        if !correct {
+            self.session_mistakes += 1;
+            *self.session_misses.entry(expected).or_default() += 1;
+        }
         let log_entry = serde_json::to_vec(&KeyPress {
             correct,
             key: c,
-            time: Utc::now(),
+            expected: Some(expected),
+            time: self.clock.now(),
         })
         .unwrap();
         self.keypress_log.write_all(&log_entry)?;
         Ok(())
     }
 
-    fn load_book(book_title: &str) -> AppResult<String> {
-        Ok(deunicode(
-            &Regex::new(r"\s+")
-                .unwrap()
-                .replace_all(
-                    &fs::read_to_string(
-                        dirs::home_dir()
-                            .unwrap()
-                            .join(".booktyping")
-                            .join(format!("{}.txt", book_title)),
-                    )?
-                    .trim(),
-                    " ",
+    pub fn load_book(logs: &Logs, book_title: &str) -> AppResult<String> {
+        let text = match fs::read_to_string(logs.book_path(book_title)) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(format!(
+                    "No book named {} in {}",
+                    book_title,
+                    logs.library.display()
                 )
-                .to_string(),
+                .into())
+            }
+            Err(e) => return Err(e.into()),
+        };
+        Ok(deunicode(
+            &Regex::new(r"\s+").unwrap().replace_all(text.trim(), " "),
         ))
     }
 
-    fn get_keypress_log(book_title: &str) -> AppResult<fs::File> {
-        Ok(fs::OpenOptions::new().create(true).append(true).open(
-            dirs::home_dir()
-                .unwrap()
-                .join(".booktyping")
-                .join(book_title)
-                .join("keypresses.json"),
-        )?)
-    }
-
-    fn get_test_log(book_title: &str) -> AppResult<fs::File> {
-        Ok(fs::OpenOptions::new()
-            .create(true)
-            .read(true)
-            .write(true)
-            .open(
-                dirs::home_dir()
-                    .unwrap()
-                    .join(".booktyping")
-                    .join(book_title)
-                    .join("tests.json"),
-            )?)
-    }
-
     pub fn generate_lines(&mut self) {
-        let max_line_len = 
-            (self.terminal_width as f64 * (self.text_width_percent as f64 / 100.0)) as usize;
+        let max_line_len =
+            (self.text_area_width() as f64 * (self.text_width_percent as f64 / 100.0)) as usize;
         let mut lines = Vec::new();
         let mut line_index: Vec<(usize, usize)> = Vec::new();
         let mut line = "".to_owned();
@@ -198,18 +1076,30 @@ impl App {
         self.line_index = line_index;
     }
 
-    fn get_next_sample(test_log: &mut File, book_text: &str) -> AppResult<(usize, usize)> {
-        let mut string = String::new();
-        test_log.seek(std::io::SeekFrom::Start(0))?;
-        test_log.read_to_string(&mut string)?;
-        let tests: Vec<Test> = serde_json::from_str(&string).unwrap_or(Vec::new());
-
-        let mut start_index = 0;
-        for t in &tests {
-            if t.succeeded && t.end_index > start_index {
-                start_index = t.end_index;
+    /// Chooses the sample after one which has ended.
+    fn next_sample(&mut self, succeeded: bool) -> AppResult<(usize, usize)> {
+        if let Mode::Drill = self.mode {
+            // Retry a failed passage, and start over after the last one.
+            if succeeded {
+                self.drill_index = (self.drill_index + 1) % self.drill_passages.len();
             }
+            return Ok(self.drill_passages[self.drill_index]);
         }
+        Ok(App::get_next_sample(
+            &self.tests,
+            &self.timed_tests,
+            &self.positions,
+            &self.book_text,
+        ))
+    }
+
+    fn get_next_sample(
+        tests: &[Test],
+        timed_tests: &[TimedTest],
+        positions: &[Position],
+        book_text: &str,
+    ) -> (usize, usize) {
+        let start_index = log::book_position(tests, timed_tests, positions);
 
         let avg_50 = tests
             .iter()
@@ -254,58 +1144,83 @@ impl App {
 
         let start_index = usize::min(start_index, book_text.len() - 1);
         let len = usize::min(len, book_text.len() - start_index - 1);
-        Ok((start_index, len))
+        (start_index, len)
     }
 
-    pub fn get_rolling_average(&mut self) -> AppResult<usize> {
-        let mut string = String::new();
-        self.test_log.seek(std::io::SeekFrom::Start(0))?;
-        self.test_log.read_to_string(&mut string)?;
-        let tests: Vec<Test> = serde_json::from_str(&string).unwrap_or(Vec::new());
-
-        Ok(tests
+    pub fn get_rolling_average(&self) -> usize {
+        self.tests
             .iter()
             .map(|t| t.end_index - t.start_index)
             .filter(|&len| len > 5)
             .rev()
             .take(10)
             .sum::<usize>()
-            / 10)
+            / 10
+    }
+
+    /// Average speed and accuracy of the last 10 samples which recorded them.
+    pub fn get_rolling_speed(&self) -> Option<(f64, f64)> {
+        let recent = self
+            .tests
+            .iter()
+            .rev()
+            .filter_map(|t| Some((t.wpm?, t.accuracy?)))
+            .take(10)
+            .collect::<Vec<_>>();
+        if recent.is_empty() {
+            return None;
+        }
+        let n = recent.len() as f64;
+        Some((
+            recent.iter().map(|&(wpm, _)| wpm).sum::<f64>() / n,
+            recent.iter().map(|&(_, accuracy)| accuracy).sum::<f64>() / n,
+        ))
     }
 
-    fn log_test(&mut self, succeeded: bool) -> AppResult<()> {
-        let mut string = String::new();
-        self.test_log.seek(std::io::SeekFrom::Start(0))?;
-        self.test_log.read_to_string(&mut string)?;
-        let mut tests: Vec<Test> = serde_json::from_str(&string).unwrap_or(Vec::new());
+    /// Logs the sample which has ended, failed by typing `mistyped` if it
+    /// has a value, keeping drills apart so that they don't move the book
+    /// position.
+    fn log_test(&mut self, mistyped: Option<char>) -> AppResult<()> {
+        let completed = self.clock.now();
+        // Timed from the first keypress, like the live speed.
+        let started = self.attempt_started.unwrap_or(completed);
+        let minutes = self.active_time(started, completed).num_milliseconds() as f64 / 60_000.0;
+        let (test_log, tests) = match self.mode {
+            Mode::Drill => (&mut self.drill_log, &mut self.drills),
+            _ => (&mut self.test_log, &mut self.tests),
+        };
         tests.push(Test {
-            succeeded,
+            succeeded: mistyped.is_none(),
             start_index: self.sample_start_index,
             end_index: self.sample_start_index + self.cur_char,
-            started: self.start_time,
-            completed: Utc::now(),
+            started,
+            completed,
+            wpm: Some(if minutes > 0.0 {
+                self.cur_char as f64 / 5.0 / minutes
+            } else {
+                0.0
+            }),
+            accuracy: Some(
+                (self.keypresses - self.mistakes) as f64 / usize::max(self.keypresses, 1) as f64,
+            ),
+            pauses: std::mem::take(&mut self.pauses),
+            mistyped,
         });
-        self.test_log.seek(std::io::SeekFrom::Start(0))?;
-        self.test_log.write_all(&serde_json::to_vec(&tests)?)?;
+        test_log.seek(std::io::SeekFrom::Start(0))?;
+        test_log.write_all(&serde_json::to_vec(tests)?)?;
+        if self.mode != Mode::Drill {
+            self.update_progress();
+        }
         Ok(())
     }
-}
 
-#[derive(Serialize, Deserialize)]
-struct KeyPress {
-    correct: bool,
-    key: char,
-    #[serde(with = "ts_nanoseconds")]
-    time: DateTime<Utc>,
-}
-
-#[derive(Serialize, Deserialize)]
-struct Test {
-    succeeded: bool,
-    start_index: usize,
-    end_index: usize,
-    #[serde(with = "ts_nanoseconds")]
-    started: DateTime<Utc>,
-    #[serde(with = "ts_nanoseconds")]
-    completed: DateTime<Utc>,
+    fn update_progress(&mut self) {
+        self.progress = Progress::new(
+            &self.book_text,
+            &self.chapters,
+            &self.tests,
+            &self.timed_tests,
+            &self.positions,
+        );
+    }
 }
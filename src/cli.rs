@@ -0,0 +1,210 @@
+use crate::app::Mode;
+use crate::export;
+use crate::replay;
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use std::path::PathBuf;
+
+/// Default length of a timed test, in minutes.
+const DEFAULT_TIMED_MINUTES: i64 = 1;
+
+pub const USAGE: &str = "\
+Usage:
+    booktyping [list]                  list your books and your progress through them
+    booktyping <book>                  practice adaptive samples
+    booktyping timed <book> [minutes]  type continuously until the clock runs out
+    booktyping drill <book>            practice passages dense in your weak keys
+    booktyping replay <book> [--from <time>] [--to <time>] [--speed <x>]
+                                       watch recorded typing, by default your last session
+    booktyping stats <book> [--json]   print a summary of your practice
+    booktyping export <book> [--format csv] [--from YYYY-MM-DD] [--to YYYY-MM-DD] [--out <dir>]
+                                       write your tests and keypresses to CSV files
+    booktyping profiles [<book>]       list the profiles, or compare them on a book
+
+Add --profile <name> to any command to use the logs of that profile, which
+is created when you first practice with it.
+Add --record <file> when practicing to save the terminal events to a file,
+to reproduce a problem.";
+
+/// Command requested on the command line.
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    /// Open the typing interface for a book, recording its events to a file
+    /// if one is given.
+    Practice {
+        book_title: String,
+        mode: Mode,
+        record: Option<PathBuf>,
+    },
+    /// List the books in the library.
+    List,
+    /// Print a summary of the practice logs of a book.
+    Stats { book_title: String, json: bool },
+    /// Write the practice logs of a book to CSV files.
+    Export {
+        book_title: String,
+        options: export::Options,
+    },
+    /// List the profiles, or compare them on a book.
+    Profiles { book_title: Option<String> },
+}
+
+/// Parses the command line arguments, excluding the program name.
+pub fn parse(args: &[String]) -> Result<Command, String> {
+    let (args, record) = split_record(args)?;
+    match (parse_command(&args)?, record) {
+        (Command::Practice { book_title, mode, .. }, record) => Ok(Command::Practice {
+            book_title,
+            mode,
+            record,
+        }),
+        (_, Some(_)) => Err("Only practice can be recorded".to_owned()),
+        (command, None) => Ok(command),
+    }
+}
+
+/// Takes the `--record <file>` flag out of the arguments.
+pub fn split_record(args: &[String]) -> Result<(Vec<String>, Option<PathBuf>), String> {
+    let (args, record) = split_flag(args, "--record")?;
+    Ok((args, record.map(PathBuf::from)))
+}
+
+/// Takes the `--profile <name>` flag out of the arguments.
+pub fn split_profile(args: &[String]) -> Result<(Vec<String>, Option<String>), String> {
+    split_flag(args, "--profile")
+}
+
+/// Takes a flag which can be given with any command, and its value, out of
+/// the arguments.
+fn split_flag(args: &[String], flag: &str) -> Result<(Vec<String>, Option<String>), String> {
+    let mut args = args.to_vec();
+    let value = match args.iter().position(|arg| arg == flag) {
+        Some(i) if i + 1 < args.len() => {
+            let value = args.remove(i + 1);
+            args.remove(i);
+            Some(value)
+        }
+        Some(_) => return Err(USAGE.to_owned()),
+        None => None,
+    };
+    Ok((args, value))
+}
+
+fn parse_command(args: &[String]) -> Result<Command, String> {
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+    match args.as_slice() {
+        [] | ["list"] => Ok(Command::List),
+        ["timed", book_title, rest @ ..] => {
+            let minutes = match rest {
+                [] => DEFAULT_TIMED_MINUTES,
+                [minutes] => minutes
+                    .parse::<i64>()
+                    .ok()
+                    .filter(|&m| m > 0)
+                    .ok_or_else(|| format!("Invalid number of minutes: {}", minutes))?,
+                _ => return Err(USAGE.to_owned()),
+            };
+            Ok(Command::Practice {
+                book_title: book_title.to_string(),
+                mode: Mode::Timed(chrono::Duration::minutes(minutes)),
+                record: None,
+            })
+        }
+        ["drill", book_title] => Ok(Command::Practice {
+            book_title: book_title.to_string(),
+            mode: Mode::Drill,
+            record: None,
+        }),
+        ["stats", book_title] => Ok(Command::Stats {
+            book_title: book_title.to_string(),
+            json: false,
+        }),
+        ["stats", book_title, "--json"] | ["stats", "--json", book_title] => Ok(Command::Stats {
+            book_title: book_title.to_string(),
+            json: true,
+        }),
+        ["export", book_title, rest @ ..] => Ok(Command::Export {
+            book_title: book_title.to_string(),
+            options: parse_export_options(rest)?,
+        }),
+        ["profiles"] => Ok(Command::Profiles { book_title: None }),
+        ["profiles", book_title] => Ok(Command::Profiles {
+            book_title: Some(book_title.to_string()),
+        }),
+        ["replay", book_title, rest @ ..] => Ok(Command::Practice {
+            book_title: book_title.to_string(),
+            mode: Mode::Replay(parse_replay_window(rest)?),
+            record: None,
+        }),
+        [book_title] => Ok(Command::Practice {
+            book_title: book_title.to_string(),
+            mode: Mode::Samples,
+            record: None,
+        }),
+        _ => Err(USAGE.to_owned()),
+    }
+}
+
+/// Parses the flags of the export command.
+fn parse_export_options(args: &[&str]) -> Result<export::Options, String> {
+    let mut options = export::Options {
+        from: None,
+        to: None,
+        out_dir: PathBuf::from("."),
+    };
+    let parse_date = |date: &str| {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|_| format!("Invalid date, expected YYYY-MM-DD: {}", date))
+    };
+    let mut args = args.iter();
+    while let Some(&flag) = args.next() {
+        let value = *args.next().ok_or_else(|| USAGE.to_owned())?;
+        match flag {
+            "--format" if value == "csv" => {}
+            "--format" => return Err(format!("Unsupported export format: {}", value)),
+            "--from" => options.from = Some(parse_date(value)?),
+            "--to" => options.to = Some(parse_date(value)?),
+            "--out" => options.out_dir = PathBuf::from(value),
+            _ => return Err(USAGE.to_owned()),
+        }
+    }
+    Ok(options)
+}
+
+/// Parses the flags of the replay command.
+fn parse_replay_window(args: &[&str]) -> Result<replay::Window, String> {
+    let mut window = replay::Window {
+        from: None,
+        to: None,
+        speed: 1.0,
+    };
+    let mut args = args.iter();
+    while let Some(&flag) = args.next() {
+        let value = *args.next().ok_or_else(|| USAGE.to_owned())?;
+        match flag {
+            "--from" => window.from = Some(parse_time(value, NaiveTime::MIN)?),
+            "--to" => window.to = Some(parse_time(value, NaiveTime::from_hms_opt(23, 59, 59).unwrap())?),
+            "--speed" => {
+                window.speed = value
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|&s| s > 0.0)
+                    .ok_or_else(|| format!("Invalid speed: {}", value))?
+            }
+            _ => return Err(USAGE.to_owned()),
+        }
+    }
+    Ok(window)
+}
+
+/// Parses a local time written as `YYYY-MM-DD`, `YYYY-MM-DD HH:MM` or
+/// `YYYY-MM-DD HH:MM:SS`, taking a bare date to be at `time_of_day`.
+fn parse_time(time: &str, time_of_day: NaiveTime) -> Result<DateTime<Utc>, String> {
+    let time = time.replace('T', " ");
+    ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(&time, format).ok())
+        .or_else(|| Some(NaiveDate::parse_from_str(&time, "%Y-%m-%d").ok()?.and_time(time_of_day)))
+        .and_then(|time| Local.from_local_datetime(&time).earliest())
+        .map(|time| time.with_timezone(&Utc))
+        .ok_or_else(|| format!("Invalid time, expected YYYY-MM-DD [HH:MM[:SS]]: {}", time))
+}
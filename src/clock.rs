@@ -0,0 +1,44 @@
+use chrono::{DateTime, Duration, Local, Utc};
+use std::cell::Cell;
+use std::rc::Rc;
+
+/// Source of the current time, which can be set by hand to make runs of the
+/// app repeatable.
+#[derive(Debug, Clone, Default)]
+pub enum Clock {
+    /// The time of the system, shown in its time zone.
+    #[default]
+    System,
+    /// A time which only changes when it is advanced, shown in UTC. Clones
+    /// share the same time.
+    Manual(Rc<Cell<DateTime<Utc>>>),
+}
+
+impl Clock {
+    /// Clock which starts at `time` and stays there until it is advanced.
+    pub fn manual(time: DateTime<Utc>) -> Self {
+        Clock::Manual(Rc::new(Cell::new(time)))
+    }
+
+    pub fn now(&self) -> DateTime<Utc> {
+        match self {
+            Clock::System => Utc::now(),
+            Clock::Manual(time) => time.get(),
+        }
+    }
+
+    /// Moves a manual clock forward. The system clock can't be moved.
+    pub fn advance(&self, duration: Duration) {
+        if let Clock::Manual(time) = self {
+            time.set(time.get() + duration);
+        }
+    }
+
+    /// Time of day of the current time, as hours and minutes.
+    pub fn time_of_day(&self) -> String {
+        match self {
+            Clock::System => Local::now().format("%H:%M").to_string(),
+            Clock::Manual(time) => time.get().format("%H:%M").to_string(),
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use crate::app::AppResult;
+use crate::keymap::Action;
+use crate::log::Logs;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt, fs};
+
+/// User settings, read from `config.json` in the directory of the profile,
+/// or else in the library directory.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub daily_goal: Option<Goal>,
+    /// Name of the color theme, either built in or one of `themes`.
+    pub theme: Option<String>,
+    /// Themes defined by the user, by name.
+    pub themes: HashMap<String, ThemeConfig>,
+    /// Names of the keys bound to actions, replacing their default keys.
+    pub keys: HashMap<Action, Vec<String>>,
+    /// Whether the mouse is captured for scrolling and clicking, which stops
+    /// the terminal selecting text.
+    pub mouse: bool,
+    /// Seconds without a keypress after which the clock is paused, or 0 to
+    /// never pause it.
+    pub idle_seconds: u64,
+    /// Milliseconds the mistake which failed a sample is shown for, unless a
+    /// key is typed first, or 0 to not show it.
+    pub mistake_millis: u64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            daily_goal: None,
+            theme: None,
+            themes: HashMap::new(),
+            keys: HashMap::new(),
+            mouse: true,
+            idle_seconds: 10,
+            mistake_millis: 1500,
+        }
+    }
+}
+
+impl Config {
+    /// Loads the config file of a profile, or the defaults if there isn't one.
+    pub fn load(logs: &Logs) -> AppResult<Self> {
+        let path = logs.profile_dir().join("config.json");
+        let path = match path.exists() {
+            true => path,
+            false => logs.library.join("config.json"),
+        };
+        match fs::read_to_string(&path) {
+            Ok(string) => serde_json::from_str(&string)
+                .map_err(|e| format!("Invalid config file {}: {}", path.display(), e).into()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// Amount of practice to do each day, written in the config as e.g.
+/// `{"minutes": 15}`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Goal {
+    /// Minutes of practice.
+    Minutes(u32),
+    /// Characters advanced through books.
+    Characters(usize),
+    /// Samples passed.
+    Samples(usize),
+}
+
+impl Goal {
+    pub fn target(&self) -> f64 {
+        match *self {
+            Goal::Minutes(minutes) => minutes as f64,
+            Goal::Characters(characters) => characters as f64,
+            Goal::Samples(samples) => samples as f64,
+        }
+    }
+
+    pub fn unit(&self) -> &'static str {
+        match self {
+            Goal::Minutes(_) => "min",
+            Goal::Characters(_) => "chars",
+            Goal::Samples(_) => "samples",
+        }
+    }
+}
+
+impl fmt::Display for Goal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.target(), self.unit())
+    }
+}
+
+/// Theme defined in the config, as changes to the styles of a built-in theme.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeConfig {
+    /// Built-in theme the styles which aren't given are taken from.
+    pub base: Option<String>,
+    pub typed: Option<StyleConfig>,
+    pub current: Option<StyleConfig>,
+    pub pending: Option<StyleConfig>,
+    pub context: Option<StyleConfig>,
+    pub border: Option<StyleConfig>,
+    pub ghost: Option<StyleConfig>,
+    pub search: Option<StyleConfig>,
+    pub mistake: Option<StyleConfig>,
+    pub gauge: Option<StyleConfig>,
+    pub passed: Option<StyleConfig>,
+    pub failed: Option<StyleConfig>,
+    pub chart_length: Option<StyleConfig>,
+    pub chart_wpm: Option<StyleConfig>,
+    pub chart_accuracy: Option<StyleConfig>,
+    pub heat_low: Option<StyleConfig>,
+    pub heat_mid: Option<StyleConfig>,
+    pub heat_high: Option<StyleConfig>,
+    pub heat_none: Option<StyleConfig>,
+}
+
+/// Style written in the config, e.g.
+/// `{"fg": "black", "bg": "#ffcc00", "modifiers": ["bold"]}`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StyleConfig {
+    pub fg: Option<String>,
+    pub bg: Option<String>,
+    pub modifiers: Vec<String>,
+}
@@ -0,0 +1,100 @@
+use crate::analysis::{self, base_key};
+use crate::log::KeyPress;
+
+/// Number of weak keys and bigrams a drill targets.
+const WEAK_KEYS: usize = 5;
+const WEAK_BIGRAMS: usize = 10;
+/// Fewest times a key must have been expected for it to count as weak.
+const MIN_KEY_ATTEMPTS: usize = 10;
+/// Approximate length of a drill passage, in characters.
+pub const PASSAGE_LEN: usize = 80;
+/// Number of passages in a drill.
+pub const PASSAGES: usize = 20;
+
+/// Keys and bigrams a drill practices.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Targets {
+    /// Keys as returned by [`base_key`].
+    pub keys: Vec<char>,
+    pub bigrams: Vec<String>,
+}
+
+impl Targets {
+    /// Finds the keys missed most often and the slowest bigrams.
+    pub fn from_keypresses(keypresses: &[KeyPress]) -> Self {
+        let mut keys = analysis::key_stats(keypresses)
+            .into_iter()
+            .filter(|(_, s)| s.expected >= MIN_KEY_ATTEMPTS && s.missed > 0)
+            .collect::<Vec<_>>();
+        keys.sort_by(|(a, a_stats), (b, b_stats)| {
+            b_stats
+                .miss_rate()
+                .partial_cmp(&a_stats.miss_rate())
+                .unwrap()
+                .then(a.cmp(b))
+        });
+        Targets {
+            keys: keys.into_iter().take(WEAK_KEYS).map(|(k, _)| k).collect(),
+            bigrams: analysis::slowest_ngrams(keypresses, 2, WEAK_BIGRAMS)
+                .into_iter()
+                .map(|s| s.ngram)
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty() && self.bigrams.is_empty()
+    }
+}
+
+/// Finds the passages of the book with the most targets per character, as
+/// `(start_index, len)` pairs which start and end at word boundaries.
+pub fn find_passages(book_text: &str, targets: &Targets, count: usize) -> Vec<(usize, usize)> {
+    let chars = book_text.chars().collect::<Vec<_>>();
+
+    // Number of targets ending before each index.
+    let mut hits = vec![0; chars.len() + 1];
+    for (i, &c) in chars.iter().enumerate() {
+        let mut hit = targets.keys.contains(&base_key(c)) as usize;
+        if i > 0 {
+            let bigram = [chars[i - 1], c].iter().collect::<String>();
+            hit += targets.bigrams.contains(&bigram) as usize;
+        }
+        hits[i + 1] = hits[i] + hit;
+    }
+
+    let word_starts = (0..chars.len())
+        .filter(|&i| i == 0 || chars[i - 1] == ' ')
+        .collect::<Vec<_>>();
+    let mut candidates = Vec::new();
+    let mut end_word = 0;
+    for (i, &start) in word_starts.iter().enumerate() {
+        // End each passage at the last word start within PASSAGE_LEN, so it
+        // includes the space before the next word like a regular sample.
+        end_word = usize::max(end_word, i + 1);
+        while end_word + 1 < word_starts.len() && word_starts[end_word + 1] - start <= PASSAGE_LEN
+        {
+            end_word += 1;
+        }
+        let Some(&end) = word_starts.get(end_word) else {
+            break;
+        };
+        let score = (hits[end] - hits[start]) as f64 / (end - start) as f64;
+        candidates.push((score, start, end - start));
+    }
+    candidates.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap().then(a.1.cmp(&b.1)));
+
+    let mut passages: Vec<(usize, usize)> = Vec::new();
+    for (_, start, len) in candidates {
+        if passages.len() == count {
+            break;
+        }
+        if passages
+            .iter()
+            .all(|&(s, l)| start + len <= s || s + l <= start)
+        {
+            passages.push((start, len));
+        }
+    }
+    passages
+}
@@ -1,14 +1,19 @@
 use crate::app::AppResult;
-use crossterm::event::{self, Event as CrosstermEvent, KeyEvent};
+use crossterm::event::{self, Event as CrosstermEvent, KeyEvent, MouseEvent};
+use serde::{Deserialize, Serialize};
 use std::sync::mpsc;
 use std::thread;
 use std::time::{Duration, Instant};
 
 /// Terminal events.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub enum Event {
+    /// Terminal tick.
+    Tick,
     /// Key press.
     Key(KeyEvent),
+    /// Mouse click or scroll.
+    Mouse(MouseEvent),
     /// Terminal resize.
     Resize(u16, u16),
 }
@@ -42,6 +47,7 @@ impl EventHandler {
                     if event::poll(timeout).expect("no events available") {
                         match event::read().expect("unable to read event") {
                             CrosstermEvent::Key(e) => sender.send(Event::Key(e)),
+                            CrosstermEvent::Mouse(e) => sender.send(Event::Mouse(e)),
                             CrosstermEvent::Resize(w, h) => sender.send(Event::Resize(w, h)),
                             _ => Ok(()),
                         }
@@ -49,6 +55,7 @@ impl EventHandler {
                     }
 
                     if last_tick.elapsed() >= tick_rate {
+                        sender.send(Event::Tick).expect("failed to send tick event");
                         last_tick = Instant::now();
                     }
                 }
@@ -0,0 +1,181 @@
+use crate::analysis;
+use crate::app::{App, AppResult};
+use crate::log::{KeyPress, Logs, Test, TimedTest};
+use chrono::{DateTime, Local, NaiveDate, SecondsFormat, Utc};
+use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
+
+/// Options of the export command.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    /// First day to export, in local time.
+    pub from: Option<NaiveDate>,
+    /// Last day to export, in local time.
+    pub to: Option<NaiveDate>,
+    /// Directory the files are written to.
+    pub out_dir: PathBuf,
+}
+
+impl Options {
+    fn includes(&self, time: DateTime<Utc>) -> bool {
+        let date = time.with_timezone(&Local).date_naive();
+        self.from.is_none_or(|from| date >= from) && self.to.is_none_or(|to| date <= to)
+    }
+}
+
+/// Writes the tests and keypresses of a book to CSV files.
+pub fn run(logs: &Logs, book_title: &str, options: &Options) -> AppResult<()> {
+    let book_text = App::load_book(logs, book_title)?;
+    let tests = logs.read_tests(book_title)?;
+    let drills = logs.read_drills(book_title)?;
+    let timed_tests = logs.read_timed_tests(book_title)?;
+    let keypresses = logs.read_keypresses(book_title)?;
+
+    fs::create_dir_all(&options.out_dir)?;
+    let tests_path = options.out_dir.join(format!("{}-tests.csv", book_title));
+    fs::write(
+        &tests_path,
+        tests_csv(&tests, &drills, &timed_tests, options),
+    )?;
+    println!("Wrote {}", tests_path.display());
+
+    let keypresses_path = options
+        .out_dir
+        .join(format!("{}-keypresses.csv", book_title));
+    let positions = analysis::keypress_placements(&keypresses, &tests, &drills, &timed_tests)
+        .into_iter()
+        .map(|p| p.map(|p| p.index))
+        .collect::<Vec<_>>();
+    fs::write(
+        &keypresses_path,
+        keypresses_csv(&book_text, &keypresses, &positions, options),
+    )?;
+    println!("Wrote {}", keypresses_path.display());
+    Ok(())
+}
+
+/// One row per sample, drill and timed test.
+fn tests_csv(
+    tests: &[Test],
+    drills: &[Test],
+    timed_tests: &[TimedTest],
+    options: &Options,
+) -> String {
+    let mut rows = Vec::new();
+    for (kind, tests) in [("sample", tests), ("drill", drills)] {
+        for t in tests.iter().filter(|t| options.includes(t.started)) {
+            rows.push((
+                t.started,
+                vec![
+                    kind.to_owned(),
+                    format_time(t.started),
+                    format_time(t.completed),
+                    t.start_index.to_string(),
+                    t.end_index.to_string(),
+                    t.length().to_string(),
+                    format_seconds(t.duration().num_milliseconds()),
+                    format_wpm(t.length(), t.duration().num_milliseconds()),
+                    t.accuracy.map(|a| format!("{:.4}", a)).unwrap_or_default(),
+                    t.succeeded.to_string(),
+                ],
+            ));
+        }
+    }
+    for t in timed_tests.iter().filter(|t| options.includes(t.started)) {
+        let length = t.end_index - t.start_index;
+        rows.push((
+            t.started,
+            vec![
+                "timed".to_owned(),
+                format_time(t.started),
+                format_time(t.completed),
+                t.start_index.to_string(),
+                t.end_index.to_string(),
+                length.to_string(),
+                format_seconds(t.duration().num_milliseconds()),
+                format_wpm(length, t.duration().num_milliseconds()),
+                format!("{:.4}", t.accuracy),
+                String::new(),
+            ],
+        ));
+    }
+    rows.sort_by_key(|&(started, _)| started);
+
+    let mut csv = String::from(
+        "kind,started,completed,start_index,end_index,length,duration_seconds,wpm,accuracy,succeeded\n",
+    );
+    for (_, row) in rows {
+        write_row(&mut csv, &row);
+    }
+    csv
+}
+
+/// One row per keypress, with the time since the previous keypress and the
+/// character the book expected.
+fn keypresses_csv(
+    book_text: &str,
+    keypresses: &[KeyPress],
+    positions: &[Option<usize>],
+    options: &Options,
+) -> String {
+    let mut csv = String::from("time,interval_ms,key,expected,correct,position\n");
+    let mut previous: Option<DateTime<Utc>> = None;
+    for (k, &position) in keypresses.iter().zip(positions) {
+        let interval = previous.map(|p| (k.time - p).num_milliseconds());
+        previous = Some(k.time);
+        if !options.includes(k.time) {
+            continue;
+        }
+        let expected = position
+            .and_then(|p| book_text.get(p..)?.chars().next())
+            .or_else(|| k.expected());
+        write_row(
+            &mut csv,
+            &[
+                format_time(k.time),
+                interval.map(|i| i.to_string()).unwrap_or_default(),
+                k.key.to_string(),
+                expected.map(String::from).unwrap_or_default(),
+                k.correct.to_string(),
+                position.map(|p| p.to_string()).unwrap_or_default(),
+            ],
+        );
+    }
+    csv
+}
+
+fn format_time(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+fn format_seconds(milliseconds: i64) -> String {
+    format!("{:.3}", milliseconds as f64 / 1000.0)
+}
+
+/// Words per minute, counting 5 characters as a word.
+fn format_wpm(length: usize, milliseconds: i64) -> String {
+    if milliseconds > 0 {
+        format!(
+            "{:.2}",
+            length as f64 / 5.0 / (milliseconds as f64 / 60_000.0)
+        )
+    } else {
+        String::new()
+    }
+}
+
+/// Appends a row, quoting fields which contain separators, quotes or spaces.
+fn write_row(csv: &mut String, fields: &[String]) {
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            csv.push(',');
+        }
+        if field.contains([',', '"', '\n', ' ']) {
+            write!(csv, "\"{}\"", field.replace('"', "\"\"")).unwrap();
+        } else {
+            csv.push_str(field);
+        }
+    }
+    csv.push('\n');
+}
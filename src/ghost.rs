@@ -0,0 +1,134 @@
+use crate::analysis;
+use crate::log::{self, KeyPress, Test};
+use chrono::Duration;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Typing of a sample from one starting position, which can be raced.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Attempt {
+    /// Time from the first keypress until each character was typed correctly.
+    pub times: Vec<Duration>,
+}
+
+impl Attempt {
+    /// Number of characters typed after `elapsed` time.
+    pub fn position(&self, elapsed: Duration) -> usize {
+        self.times.partition_point(|&t| t <= elapsed)
+    }
+
+    /// Whether this attempt got further than another, or as far in less time.
+    pub fn beats(&self, other: &Attempt) -> bool {
+        match self.times.len().cmp(&other.times.len()) {
+            std::cmp::Ordering::Equal => self.times.last() < other.times.last(),
+            ordering => ordering.is_gt(),
+        }
+    }
+}
+
+/// Best earlier attempt from each starting position.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Ghosts {
+    best: HashMap<usize, Attempt>,
+}
+
+impl Ghosts {
+    /// Finds the best attempts in the logs of samples and drills.
+    pub fn from_logs(keypresses: &[KeyPress], tests: &[Test], drills: &[Test]) -> Self {
+        let mut ghosts = Ghosts::default();
+        let mut current = None;
+        let mut attempt = Attempt::default();
+        let placements = analysis::keypress_placements(keypresses, tests, drills, &[]);
+        for (k, placement) in keypresses.iter().zip(placements) {
+            let Some(p) = placement else {
+                continue;
+            };
+            if current.map(|(run, _)| run) != Some((p.start_index, p.started)) {
+                if let Some(((start_index, _), _)) = current {
+                    ghosts.add(start_index, std::mem::take(&mut attempt));
+                }
+                current = Some(((p.start_index, p.started), k.time));
+            }
+            let (_, first_keypress) = current.unwrap();
+            if k.correct {
+                let paused = log::paused_time(p.pauses, first_keypress, k.time);
+                attempt.times.push(k.time - first_keypress - paused);
+            }
+        }
+        if let Some(((start_index, _), _)) = current {
+            ghosts.add(start_index, attempt);
+        }
+        ghosts
+    }
+
+    pub fn get(&self, start_index: usize) -> Option<&Attempt> {
+        self.best.get(&start_index)
+    }
+
+    /// Keeps an attempt if it is the best from its starting position.
+    pub fn add(&mut self, start_index: usize, attempt: Attempt) {
+        if attempt.times.is_empty() {
+            return;
+        }
+        match self.best.get(&start_index) {
+            Some(best) if !attempt.beats(best) => {}
+            _ => {
+                self.best.insert(start_index, attempt);
+            }
+        }
+    }
+}
+
+/// Result of racing a ghost.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Outcome {
+    /// Typed further than the ghost, by a number of characters.
+    Further(usize),
+    /// Typed as far as the ghost, faster by a time.
+    Faster(Duration),
+    /// Typed as far as the ghost, slower by a time.
+    Slower(Duration),
+    /// The ghost typed further, by a number of characters.
+    Shorter(usize),
+}
+
+impl Outcome {
+    /// Compares an attempt which has ended with the ghost it raced. A
+    /// successful attempt is compared at its end even if the ghost went further.
+    pub fn new(attempt: &Attempt, ghost: &Attempt, succeeded: bool) -> Self {
+        let typed = attempt.times.len();
+        let ghost_typed = ghost.times.len();
+        if typed > ghost_typed {
+            return Outcome::Further(typed - ghost_typed);
+        }
+        if typed < ghost_typed && !succeeded {
+            return Outcome::Shorter(ghost_typed - typed);
+        }
+        let time = attempt.times.last().copied().unwrap_or_else(Duration::zero);
+        let ghost_time = ghost.times[typed.max(1) - 1];
+        if time <= ghost_time {
+            Outcome::Faster(ghost_time - time)
+        } else {
+            Outcome::Slower(time - ghost_time)
+        }
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let seconds = |d: &Duration| d.num_milliseconds() as f64 / 1000.0;
+        let chars = |n: &usize| {
+            if *n == 1 {
+                "1 char".to_owned()
+            } else {
+                format!("{} chars", n)
+            }
+        };
+        match self {
+            Outcome::Further(n) => write!(f, "Beat ghost by {}", chars(n)),
+            Outcome::Faster(time) => write!(f, "Beat ghost by {:.1}s", seconds(time)),
+            Outcome::Slower(time) => write!(f, "Ghost won by {:.1}s", seconds(time)),
+            Outcome::Shorter(n) => write!(f, "Ghost won by {}", chars(n)),
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use crate::app::AppResult;
+use crate::config::Goal;
+use crate::log::{Logs, Test, TimedTest};
+use chrono::{DateTime, Days, Local, NaiveDate, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Practice done on one day.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize)]
+pub struct DayTotals {
+    pub minutes: f64,
+    /// Characters advanced through books.
+    pub characters: usize,
+    pub samples_passed: usize,
+}
+
+impl DayTotals {
+    /// Amount done toward a goal.
+    pub fn toward(&self, goal: Goal) -> f64 {
+        match goal {
+            Goal::Minutes(_) => self.minutes,
+            Goal::Characters(_) => self.characters as f64,
+            Goal::Samples(_) => self.samples_passed as f64,
+        }
+    }
+
+    pub fn meets(&self, goal: Goal) -> bool {
+        self.toward(goal) >= goal.target()
+    }
+}
+
+/// Practice done on each day across all books, in local time.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Activity {
+    pub days: BTreeMap<NaiveDate, DayTotals>,
+}
+
+/// Local date of a time.
+pub fn local_date(time: DateTime<Utc>) -> NaiveDate {
+    time.with_timezone(&Local).date_naive()
+}
+
+impl Activity {
+    /// Loads the practice done by a profile on every book in the library.
+    pub fn load(logs: &Logs) -> AppResult<Self> {
+        let mut activity = Activity::default();
+        for book_title in logs.book_titles()? {
+            for t in logs.read_tests(&book_title)? {
+                activity.add_test(&t, true);
+            }
+            for t in logs.read_drills(&book_title)? {
+                activity.add_test(&t, false);
+            }
+            for t in logs.read_timed_tests(&book_title)? {
+                activity.add_timed_test(&t);
+            }
+        }
+        Ok(activity)
+    }
+
+    /// Adds a sample, counting the characters typed if it moved the book position.
+    pub fn add_test(&mut self, t: &Test, advances: bool) {
+        let day = self.days.entry(local_date(t.completed)).or_default();
+        day.minutes += t.duration().num_milliseconds() as f64 / 60_000.0;
+        if t.succeeded {
+            day.samples_passed += 1;
+            if advances {
+                day.characters += t.length();
+            }
+        }
+    }
+
+    pub fn add_timed_test(&mut self, t: &TimedTest) {
+        let day = self.days.entry(local_date(t.completed)).or_default();
+        day.minutes += t.duration().num_milliseconds() as f64 / 60_000.0;
+        day.characters += t.end_index - t.start_index;
+    }
+
+    pub fn day(&self, date: NaiveDate) -> DayTotals {
+        self.days.get(&date).copied().unwrap_or_default()
+    }
+
+    /// Number of consecutive days up to `today` on which `counts` holds. A
+    /// streak isn't broken until a whole day passes without it holding.
+    pub fn current_streak(&self, today: NaiveDate, counts: impl Fn(&DayTotals) -> bool) -> usize {
+        let mut date = today;
+        if !counts(&self.day(date)) {
+            date = date - Days::new(1);
+        }
+        let mut streak = 0;
+        while counts(&self.day(date)) {
+            streak += 1;
+            date = date - Days::new(1);
+        }
+        streak
+    }
+
+    /// Length of the longest run of consecutive days on which `counts` holds.
+    pub fn longest_streak(&self, counts: impl Fn(&DayTotals) -> bool) -> usize {
+        let mut longest = 0;
+        let mut streak = 0;
+        let mut previous: Option<NaiveDate> = None;
+        for (&date, day) in &self.days {
+            if !counts(day) {
+                streak = 0;
+                continue;
+            }
+            let consecutive = previous.is_some_and(|p| p + Days::new(1) == date);
+            streak = if consecutive { streak + 1 } else { 1 };
+            previous = Some(date);
+            longest = usize::max(longest, streak);
+        }
+        longest
+    }
+}
+
+/// Formats a number of days.
+pub fn format_days(days: usize) -> String {
+    if days == 1 {
+        "1 day".to_owned()
+    } else {
+        format!("{} days", days)
+    }
+}
+
+/// Whether any practice was done on a day.
+pub fn practiced(day: &DayTotals) -> bool {
+    day.minutes > 0.0 || day.samples_passed > 0 || day.characters > 0
+}
+
+/// Daily goal and streaks, as reported by the stats command.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GoalReport {
+    pub daily_goal: Option<Goal>,
+    /// Amount done toward the goal today.
+    pub today: Option<f64>,
+    /// Consecutive days on which the goal was met.
+    pub goal_streak: Option<usize>,
+    /// Consecutive days with any practice.
+    pub current_streak: usize,
+    pub longest_streak: usize,
+}
+
+impl GoalReport {
+    pub fn new(activity: &Activity, goal: Option<Goal>, today: NaiveDate) -> Self {
+        GoalReport {
+            daily_goal: goal,
+            today: goal.map(|goal| activity.day(today).toward(goal)),
+            goal_streak: goal.map(|goal| activity.current_streak(today, |day| day.meets(goal))),
+            current_streak: activity.current_streak(today, practiced),
+            longest_streak: activity.longest_streak(practiced),
+        }
+    }
+}
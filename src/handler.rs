@@ -1,13 +1,225 @@
-use crate::app::{App, AppResult, DEFAULT_TEXT_WIDTH_PERCENT, FULL_TEXT_WIDTH_PERCENT};
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crate::app::{
+    App, AppResult, Mode, Overlay, DEFAULT_TEXT_WIDTH_PERCENT, FULL_TEXT_WIDTH_PERCENT,
+};
+use crate::event::Event;
+use crate::keymap::{Action, Context};
+use crate::search::Search;
+use crate::replay::{LONG_SEEK_SECONDS, SEEK_SECONDS};
+use chrono::Duration;
+use crossterm::event::{
+    KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+
+/// Handles an event from the terminal.
+pub fn handle_event(event: Event, app: &mut App) -> AppResult<()> {
+    match event {
+        Event::Tick => app.tick(),
+        Event::Key(key_event) => handle_key_events(key_event, app),
+        Event::Mouse(mouse_event) => handle_mouse_events(mouse_event, app),
+        Event::Resize(width, _) => {
+            app.terminal_width = width;
+            app.generate_lines();
+            Ok(())
+        }
+    }
+}
 
 /// Handles the key events and updates the state of [`App`].
 pub fn handle_key_events(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
-    use KeyModifiers as M;
-    use KeyCode as C;
-    match (key_event.modifiers, key_event.code) {
-        (M::CONTROL, C::Char('c')) => app.quit(),
-        (M::CONTROL, C::Char('f')) => {
+    if app.summary.is_some() {
+        app.quit();
+        return Ok(());
+    }
+    if app.search.as_ref().is_some_and(|search| search.editing) {
+        return handle_search_key(key_event, app);
+    }
+    if app.bookmark_name.is_some() {
+        return handle_bookmark_name_key(key_event, app);
+    }
+    if app.profile_picker.is_some() {
+        return handle_profile_picker_key(key_event, app);
+    }
+    // Keys which aren't bound only count as typing when nothing is drawn
+    // over the text and it isn't being replayed.
+    let active: &[Context] = match (app.overlay, &app.replay) {
+        (Some(_), _) => &[Context::Global, Context::Overlay],
+        (None, Some(_)) => &[Context::Global, Context::Text, Context::Replay],
+        (None, None) => &[Context::Global, Context::Text],
+    };
+    if let Some(Overlay::Help) = app.overlay {
+        // The scrolling keys scroll the help rather than the text under it.
+        match app.keymap.action(&key_event, &[Context::Text]) {
+            Some(Action::ScrollUp) => app.help_scroll = app.help_scroll.saturating_sub(1),
+            Some(Action::ScrollDown) => app.help_scroll += 1,
+            Some(Action::ScrollPageUp) => app.help_scroll = app.help_scroll.saturating_sub(10),
+            Some(Action::ScrollPageDown) => app.help_scroll += 10,
+            _ => {}
+        }
+    }
+    if let Some(Overlay::Bookmarks) = app.overlay {
+        // The scrolling keys move the selection.
+        let last = app.bookmarks.len().saturating_sub(1);
+        match app.keymap.action(&key_event, &[Context::Text]) {
+            Some(Action::ScrollUp) => {
+                app.selected_bookmark = app.selected_bookmark.saturating_sub(1)
+            }
+            Some(Action::ScrollDown) => {
+                app.selected_bookmark = usize::min(app.selected_bookmark + 1, last)
+            }
+            _ => {}
+        }
+    }
+    match app.keymap.action(&key_event, active) {
+        Some(action) => handle_action(action, app)?,
+        None => {
+            if let (None, None, KeyCode::Char(c)) = (app.overlay, &app.replay, key_event.code) {
+                app.handle_char(c)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Edits the query in the search prompt. Only global actions work while it is
+/// open, and nothing typed into it counts as typing.
+fn handle_search_key(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
+    let Some(search) = &mut app.search else {
+        return Ok(());
+    };
+    match key_event.code {
+        KeyCode::Esc => app.search = None,
+        KeyCode::Enter if search.query.is_empty() => app.search = None,
+        KeyCode::Enter => search.editing = false,
+        KeyCode::Backspace => {
+            search.query.pop();
+            app.update_search();
+        }
+        KeyCode::Char(c)
+            if !key_event
+                .modifiers
+                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+        {
+            search.query.push(c);
+            app.update_search();
+        }
+        _ => {
+            if let Some(action) = app.keymap.action(&key_event, &[Context::Global]) {
+                handle_action(action, app)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Moves the selection of the profile picker, or edits the name of a new
+/// profile, and switches to the profile chosen on Enter. Only global actions
+/// work while it is open.
+fn handle_profile_picker_key(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
+    let Some(picker) = &mut app.profile_picker else {
+        return Ok(());
+    };
+    match key_event.code {
+        KeyCode::Esc => app.profile_picker = None,
+        KeyCode::Up => picker.selected = picker.selected.saturating_sub(1),
+        KeyCode::Down => {
+            picker.selected = usize::min(picker.selected + 1, picker.standings.len() - 1)
+        }
+        KeyCode::Enter => match picker.chosen() {
+            Ok(profile) => app.switch_profile(profile)?,
+            Err(e) => picker.error = Some(e),
+        },
+        KeyCode::Backspace => {
+            picker.name.pop();
+        }
+        KeyCode::Char(c)
+            if !key_event
+                .modifiers
+                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+        {
+            picker.name.push(c)
+        }
+        _ => {
+            if let Some(action) = app.keymap.action(&key_event, &[Context::Global]) {
+                handle_action(action, app)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Edits the name of a new bookmark, which is added on Enter.
+fn handle_bookmark_name_key(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
+    let Some(name) = &mut app.bookmark_name else {
+        return Ok(());
+    };
+    match key_event.code {
+        KeyCode::Esc => app.bookmark_name = None,
+        KeyCode::Enter => {
+            let name = app.bookmark_name.take().unwrap();
+            if !name.trim().is_empty() {
+                app.add_bookmark(name.trim().to_owned())?;
+            }
+        }
+        KeyCode::Backspace => {
+            name.pop();
+        }
+        KeyCode::Char(c)
+            if !key_event
+                .modifiers
+                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+        {
+            name.push(c)
+        }
+        _ => {
+            if let Some(action) = app.keymap.action(&key_event, &[Context::Global]) {
+                handle_action(action, app)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Handles the mouse events, scrolling with the wheel and inspecting the
+/// word which is clicked.
+pub fn handle_mouse_events(mouse_event: MouseEvent, app: &mut App) -> AppResult<()> {
+    // The mouse is left alone while the keyboard goes to a prompt or picker.
+    if app.summary.is_some()
+        || app.search.as_ref().is_some_and(|search| search.editing)
+        || app.bookmark_name.is_some()
+        || app.profile_picker.is_some()
+    {
+        return Ok(());
+    }
+    match (mouse_event.kind, app.overlay) {
+        (MouseEventKind::ScrollUp, Some(Overlay::Help)) => {
+            app.help_scroll = app.help_scroll.saturating_sub(1)
+        }
+        (MouseEventKind::ScrollDown, Some(Overlay::Help)) => app.help_scroll += 1,
+        (MouseEventKind::ScrollUp, _) => handle_action(Action::ScrollUp, app)?,
+        (MouseEventKind::ScrollDown, _) => handle_action(Action::ScrollDown, app)?,
+        (MouseEventKind::Down(MouseButton::Left), None | Some(Overlay::Inspect)) => {
+            match app.index_at(mouse_event.column, mouse_event.row) {
+                Some(index) => {
+                    app.inspect(index);
+                }
+                None => app.overlay = None,
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Performs the action bound to a key.
+fn handle_action(action: Action, app: &mut App) -> AppResult<()> {
+    match action {
+        Action::Help => app.toggle_help(),
+        Action::Quit => app.end_session()?,
+        Action::Pause => app.toggle_pause(),
+        Action::Heatmap => app.toggle_heatmap()?,
+        Action::Transitions => app.toggle_transitions()?,
+        Action::Charts => app.toggle_charts(),
+        Action::ToggleWidth => {
             app.full_text_width = !app.full_text_width;
             app.text_width_percent = 
                 if app.full_text_width {
@@ -18,27 +230,84 @@ pub fn handle_key_events(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
                 };
             app.generate_lines()
         }
-        (_ , C::Char(c)) => app.handle_char(c)?,
-        (M::CONTROL, C::Up) => {
+        Action::ScrollPageUp => {
             app.following_typing = false;
-            app.display_line = app.display_line.checked_sub(10).unwrap_or_default();
+            app.display_line = app.display_line.saturating_sub(10);
         }
-        (M::CONTROL, C::Down) => {
+        Action::ScrollPageDown => {
             app.following_typing = false;
             app.display_line += 10;
         }
-        (_, C::Up) => {
+        Action::ScrollUp => {
             app.following_typing = false;
-            app.display_line = app.display_line.checked_sub(1).unwrap_or_default();
+            app.display_line = app.display_line.saturating_sub(1);
         }
-        (_, C::Down) => {
+        Action::ScrollDown => {
             app.following_typing = false;
             app.display_line += 1;
         }
-        (_, C::Esc) => {
+        Action::FollowCursor => {
             app.following_typing = true;
+            app.search = None;
         }
-        _ => {}
+        Action::Search => app.search = Some(Search::new()),
+        Action::NextMatch => {
+            if let Some(search) = &mut app.search {
+                search.next();
+                app.show_search_match();
+            }
+        }
+        Action::StartHere => {
+            if let (Mode::Samples, Some(index)) = (app.mode, app.scrolled_index()) {
+                app.start_practicing(index)?;
+            }
+        }
+        Action::AddBookmark => app.bookmark_name = Some(String::new()),
+        Action::Bookmarks => app.toggle_bookmarks(),
+        Action::Profiles => app.toggle_profiles()?,
+        Action::GoToBookmark => {
+            if let Some(Overlay::Bookmarks) = app.overlay {
+                app.go_to_bookmark();
+            }
+        }
+        Action::DeleteBookmark => {
+            if let Some(Overlay::Bookmarks) = app.overlay {
+                app.delete_bookmark()?;
+            }
+        }
+        Action::PreviousMatch => {
+            if let Some(search) = &mut app.search {
+                search.previous();
+                app.show_search_match();
+            }
+        }
+        Action::CycleHeatmapView => {
+            if let Some(Overlay::Heatmap(view)) = app.overlay {
+                app.overlay = Some(Overlay::Heatmap(view.next()));
+            }
+        }
+        Action::CloseOverlay => app.overlay = None,
+        _ => handle_replay_action(action, app),
     }
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Performs an action which controls the playback of a replay.
+fn handle_replay_action(action: Action, app: &mut App) {
+    let Some(replay) = &mut app.replay else {
+        return;
+    };
+    match action {
+        Action::ReplayPause => replay.toggle_pause(),
+        Action::ReplayFaster => replay.faster(),
+        Action::ReplaySlower => replay.slower(),
+        Action::SeekBack => replay.seek(Duration::seconds(-SEEK_SECONDS)),
+        Action::SeekForward => replay.seek(Duration::seconds(SEEK_SECONDS)),
+        Action::SeekBackLong => replay.seek(Duration::seconds(-LONG_SEEK_SECONDS)),
+        Action::SeekForwardLong => replay.seek(Duration::seconds(LONG_SEEK_SECONDS)),
+        Action::ReplayStart => replay.restart(),
+        Action::ReplayEnd => replay.skip_to_end(),
+        _ => return,
+    }
+    app.show_replay_frame();
+}
@@ -0,0 +1,233 @@
+use crate::app::{App, AppResult, Mode};
+use crate::cli::{self, Command};
+use crate::clock::Clock;
+use crate::config::Config;
+use crate::event::Event;
+use crate::handler::handle_event;
+use crate::log::Logs;
+use crate::profile;
+use crate::record::Recording;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::{backend::TestBackend, buffer::Buffer, Terminal};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::{env, fs, process};
+
+/// Title of the book a harness is started on.
+pub const BOOK_TITLE: &str = "book";
+
+/// Number of libraries made by harnesses in this process, to name the next.
+static LIBRARIES: AtomicUsize = AtomicUsize::new(0);
+
+/// Time a harness starts at, unless it replays a recording.
+pub fn start_time() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap()
+}
+
+/// App run without a terminal, on a library of its own in a temporary
+/// directory and with a clock which only moves when it is advanced. It uses
+/// the default profile unless a replayed recording names another. The app
+/// is drawn to a [`TestBackend`] after every event, as in a terminal.
+pub struct Harness {
+    pub app: App,
+    terminal: Terminal<TestBackend>,
+    library: PathBuf,
+}
+
+impl Harness {
+    /// Starts practicing a book with the given text and default settings.
+    pub fn new(book_text: &str, mode: Mode, width: u16, height: u16) -> AppResult<Self> {
+        let library = new_library()?;
+        fs::write(library.join(format!("{}.txt", BOOK_TITLE)), book_text)?;
+        Harness::start(
+            Logs::new(library, None),
+            BOOK_TITLE,
+            mode,
+            Config::default(),
+            (width, height),
+            start_time(),
+        )
+    }
+
+    /// Runs the app again with the events of a recording, from the state of
+    /// the library when the recording started.
+    pub fn replay(recording: &Recording) -> AppResult<Self> {
+        let header = &recording.header;
+        let library = new_library()?;
+        header.restore_files(&library)?;
+        let (args, profile_name) = cli::split_profile(&header.args)?;
+        let logs = match profile_name {
+            Some(name) => Logs::new(library, profile::from_name(&name)?),
+            None => Logs::new(library, None),
+        };
+        let (book_title, mode) = match cli::parse(&args)? {
+            Command::Practice {
+                book_title, mode, ..
+            } => (book_title, mode),
+            _ => return Err("The recording isn't of practice".into()),
+        };
+        let config = Config::load(&logs)?;
+        let mut harness = Harness::start(
+            logs,
+            &book_title,
+            mode,
+            config,
+            (header.width, header.height),
+            header.started,
+        )?;
+        for entry in &recording.entries {
+            harness.advance(entry.time - harness.app.clock.now())?;
+            harness.event(entry.event)?;
+        }
+        Ok(harness)
+    }
+
+    fn start(
+        logs: Logs,
+        book_title: &str,
+        mode: Mode,
+        config: Config,
+        (width, height): (u16, u16),
+        time: DateTime<Utc>,
+    ) -> AppResult<Self> {
+        let library = logs.library.clone();
+        let app = App::with_clock(logs, book_title, width, mode, config, Clock::manual(time))?;
+        let mut harness = Harness {
+            app,
+            terminal: Terminal::new(TestBackend::new(width, height))?,
+            library,
+        };
+        harness.draw()?;
+        Ok(harness)
+    }
+
+    /// Runs the app again on the same library and profile, in another mode.
+    pub fn restart(&mut self, mode: Mode) -> AppResult<()> {
+        let logs = self.app.logs.clone();
+        let config = Config::load(&logs)?;
+        let width = self.terminal.size()?.width;
+        self.app = App::with_clock(logs, BOOK_TITLE, width, mode, config, self.app.clock.clone())?;
+        self.draw()
+    }
+
+    /// Handles an event and draws the app.
+    pub fn event(&mut self, event: Event) -> AppResult<()> {
+        if let Event::Resize(width, height) = event {
+            self.terminal.backend_mut().resize(width, height);
+        }
+        handle_event(event, &mut self.app)?;
+        self.draw()
+    }
+
+    pub fn key(&mut self, code: KeyCode) -> AppResult<()> {
+        self.event(Event::Key(KeyEvent::new(code, KeyModifiers::NONE)))
+    }
+
+    /// Presses a key with Ctrl held.
+    pub fn ctrl(&mut self, c: char) -> AppResult<()> {
+        self.event(Event::Key(KeyEvent::new(
+            KeyCode::Char(c),
+            KeyModifiers::CONTROL,
+        )))
+    }
+
+    /// Types each character of some text.
+    pub fn type_text(&mut self, text: &str) -> AppResult<()> {
+        for c in text.chars() {
+            self.key(KeyCode::Char(c))?;
+        }
+        Ok(())
+    }
+
+    /// Clicks the left mouse button on a cell of the screen.
+    pub fn click(&mut self, column: u16, row: u16) -> AppResult<()> {
+        self.event(Event::Mouse(MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        }))
+    }
+
+    /// Moves the clock forward, then lets the app handle a tick.
+    pub fn advance(&mut self, duration: Duration) -> AppResult<()> {
+        self.app.clock.advance(duration);
+        self.event(Event::Tick)
+    }
+
+    fn draw(&mut self) -> AppResult<()> {
+        self.terminal
+            .draw(|frame| crate::ui::render(&mut self.app, frame))?;
+        Ok(())
+    }
+
+    /// Text on the screen, one line per row.
+    pub fn screen(&self) -> String {
+        let buffer = self.buffer();
+        let mut screen = String::new();
+        for y in 0..buffer.area.height {
+            for x in 0..buffer.area.width {
+                screen.push_str(&buffer.get(x, y).symbol);
+            }
+            screen.push('\n');
+        }
+        screen
+    }
+
+    /// Cells drawn on the screen, with their styles.
+    pub fn buffer(&self) -> &Buffer {
+        self.terminal.backend().buffer()
+    }
+
+    /// Directory of the library the app is using.
+    pub fn library(&self) -> &Path {
+        &self.library
+    }
+
+    /// Checks the screen against `tests/snapshots/{name}.txt`. The snapshot is
+    /// written instead if `UPDATE_SNAPSHOTS` is set, and a missing one fails.
+    pub fn assert_snapshot(&self, name: &str) {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/snapshots")
+            .join(format!("{}.txt", name));
+        let screen = self.screen();
+        if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, &screen).unwrap();
+            return;
+        }
+        let expected = fs::read_to_string(&path).unwrap_or_else(|_| {
+            panic!(
+                "Snapshot {} is missing, run with UPDATE_SNAPSHOTS=1 to write it\nfound:\n{}",
+                path.display(),
+                screen
+            )
+        });
+        assert!(
+            screen == expected,
+            "Screen doesn't match snapshot {}\nexpected:\n{}\nfound:\n{}",
+            path.display(),
+            expected,
+            screen
+        );
+    }
+}
+
+impl Drop for Harness {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.library);
+    }
+}
+
+/// Makes an empty library directory for a harness.
+fn new_library() -> AppResult<PathBuf> {
+    let library = env::temp_dir().join(format!(
+        "booktyping-test-{}-{}",
+        process::id(),
+        LIBRARIES.fetch_add(1, Ordering::Relaxed)
+    ));
+    let _ = fs::remove_dir_all(&library);
+    fs::create_dir_all(&library)?;
+    Ok(library)
+}
@@ -0,0 +1,93 @@
+use crate::log::{Test, TimedTest};
+use chrono::{DateTime, Utc};
+
+/// Number of past outcomes listed for a word.
+pub const RECENT_OUTCOMES: usize = 10;
+
+/// How a sample or timed test which reached a word went there.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WordResult {
+    /// The whole word was typed.
+    Typed,
+    /// A mistake was made at an index in the word.
+    Failed(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Outcome {
+    pub completed: DateTime<Utc>,
+    pub timed: bool,
+    pub result: WordResult,
+    pub wpm: Option<f64>,
+}
+
+/// Past outcomes of the typing which reached a word of the book.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Inspection {
+    pub word: String,
+    pub start_index: usize,
+    pub typed: usize,
+    pub failed: usize,
+    /// Most recent outcomes, latest first.
+    pub recent: Vec<Outcome>,
+}
+
+impl Inspection {
+    /// Inspects the word around an index, or returns `None` if there is only
+    /// whitespace there.
+    pub fn new(
+        book_text: &str,
+        index: usize,
+        tests: &[Test],
+        timed_tests: &[TimedTest],
+    ) -> Option<Self> {
+        let bytes = book_text.as_bytes();
+        if bytes.get(index)?.is_ascii_whitespace() {
+            return None;
+        }
+        let start = bytes[..index]
+            .iter()
+            .rposition(u8::is_ascii_whitespace)
+            .map_or(0, |i| i + 1);
+        let end = bytes[index..]
+            .iter()
+            .position(u8::is_ascii_whitespace)
+            .map_or(bytes.len(), |i| index + i);
+
+        let mut outcomes = Vec::new();
+        for t in tests {
+            let result = if t.start_index <= start && t.end_index >= end {
+                WordResult::Typed
+            } else if !t.succeeded && (start..end).contains(&t.end_index) && t.start_index < end {
+                WordResult::Failed(t.end_index)
+            } else {
+                continue;
+            };
+            outcomes.push(Outcome {
+                completed: t.completed,
+                timed: false,
+                result,
+                wpm: t.wpm,
+            });
+        }
+        for t in timed_tests {
+            if t.start_index <= start && t.end_index >= end {
+                outcomes.push(Outcome {
+                    completed: t.completed,
+                    timed: true,
+                    result: WordResult::Typed,
+                    wpm: Some(t.net_wpm),
+                });
+            }
+        }
+        outcomes.sort_by_key(|o| std::cmp::Reverse(o.completed));
+
+        Some(Inspection {
+            word: book_text[start..end].to_owned(),
+            start_index: start,
+            typed: outcomes.iter().filter(|o| o.result == WordResult::Typed).count(),
+            failed: outcomes.iter().filter(|o| o.result != WordResult::Typed).count(),
+            recent: outcomes.into_iter().take(RECENT_OUTCOMES).collect(),
+        })
+    }
+}
@@ -0,0 +1,447 @@
+use crate::app::AppResult;
+use crate::config::Config;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Command which can be bound to keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Help,
+    Quit,
+    Pause,
+    ToggleWidth,
+    ScrollUp,
+    ScrollDown,
+    ScrollPageUp,
+    ScrollPageDown,
+    FollowCursor,
+    Search,
+    NextMatch,
+    PreviousMatch,
+    StartHere,
+    AddBookmark,
+    Bookmarks,
+    Profiles,
+    GoToBookmark,
+    DeleteBookmark,
+    Heatmap,
+    Transitions,
+    Charts,
+    CycleHeatmapView,
+    CloseOverlay,
+    ReplayPause,
+    ReplayFaster,
+    ReplaySlower,
+    SeekBack,
+    SeekForward,
+    SeekBackLong,
+    SeekForwardLong,
+    ReplayStart,
+    ReplayEnd,
+}
+
+/// Where an action can be used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Context {
+    /// Everywhere, including over overlays.
+    Global,
+    /// The text, while typing or replaying.
+    Text,
+    /// The text, while replaying.
+    Replay,
+    /// An open overlay.
+    Overlay,
+}
+
+impl Context {
+    /// Whether keys can be used in both contexts at once.
+    fn overlaps(self, other: Context) -> bool {
+        use Context::*;
+        self == other
+            || matches!(
+                (self, other),
+                (Global, _) | (_, Global) | (Text, Replay) | (Replay, Text)
+            )
+    }
+}
+
+impl Action {
+    pub const ALL: [Action; 32] = [
+        Action::Help,
+        Action::Quit,
+        Action::Pause,
+        Action::ToggleWidth,
+        Action::ScrollUp,
+        Action::ScrollDown,
+        Action::ScrollPageUp,
+        Action::ScrollPageDown,
+        Action::FollowCursor,
+        Action::Search,
+        Action::NextMatch,
+        Action::PreviousMatch,
+        Action::StartHere,
+        Action::AddBookmark,
+        Action::Bookmarks,
+        Action::Profiles,
+        Action::GoToBookmark,
+        Action::DeleteBookmark,
+        Action::Heatmap,
+        Action::Transitions,
+        Action::Charts,
+        Action::CycleHeatmapView,
+        Action::CloseOverlay,
+        Action::ReplayPause,
+        Action::ReplayFaster,
+        Action::ReplaySlower,
+        Action::SeekBack,
+        Action::SeekForward,
+        Action::SeekBackLong,
+        Action::SeekForwardLong,
+        Action::ReplayStart,
+        Action::ReplayEnd,
+    ];
+
+    pub fn context(self) -> Context {
+        match self {
+            Action::Help
+            | Action::Quit
+            | Action::Pause
+            | Action::Bookmarks
+            | Action::Profiles
+            | Action::Heatmap
+            | Action::Transitions => Context::Global,
+            Action::ToggleWidth
+            | Action::ScrollUp
+            | Action::ScrollDown
+            | Action::ScrollPageUp
+            | Action::ScrollPageDown
+            | Action::FollowCursor
+            | Action::Search
+            | Action::NextMatch
+            | Action::PreviousMatch
+            | Action::StartHere
+            | Action::AddBookmark
+            | Action::Charts => Context::Text,
+            Action::GoToBookmark
+            | Action::DeleteBookmark
+            | Action::CycleHeatmapView
+            | Action::CloseOverlay => Context::Overlay,
+            Action::ReplayPause
+            | Action::ReplayFaster
+            | Action::ReplaySlower
+            | Action::SeekBack
+            | Action::SeekForward
+            | Action::SeekBackLong
+            | Action::SeekForwardLong
+            | Action::ReplayStart
+            | Action::ReplayEnd => Context::Replay,
+        }
+    }
+
+    /// Whether the action can be used together with another one, so that
+    /// they can't share a key.
+    fn overlaps(self, other: Action) -> bool {
+        self.context().overlaps(other.context())
+            || (self.scrolls_overlays() && other.context() == Context::Overlay)
+            || (other.scrolls_overlays() && self.context() == Context::Overlay)
+    }
+
+    /// Whether the action also scrolls the help or moves through the
+    /// bookmarks while they are open.
+    fn scrolls_overlays(self) -> bool {
+        matches!(
+            self,
+            Action::ScrollUp | Action::ScrollDown | Action::ScrollPageUp | Action::ScrollPageDown
+        )
+    }
+
+    /// Keys the action is bound to unless the config changes them.
+    fn default_keys(self) -> &'static [&'static str] {
+        match self {
+            Action::Help => &["f1"],
+            Action::Quit => &["ctrl-c"],
+            Action::Pause => &["f6"],
+            Action::ToggleWidth => &["ctrl-f"],
+            Action::ScrollUp => &["up"],
+            Action::ScrollDown => &["down"],
+            Action::ScrollPageUp => &["ctrl-up"],
+            Action::ScrollPageDown => &["ctrl-down"],
+            Action::FollowCursor => &["esc"],
+            Action::Search => &["ctrl-s"],
+            Action::NextMatch => &["ctrl-n"],
+            Action::PreviousMatch => &["ctrl-p"],
+            Action::StartHere => &["ctrl-g"],
+            Action::AddBookmark => &["ctrl-b"],
+            Action::Bookmarks => &["f5"],
+            Action::Profiles => &["f7"],
+            Action::GoToBookmark => &["enter"],
+            Action::DeleteBookmark => &["delete"],
+            Action::Heatmap => &["f2"],
+            Action::Transitions => &["f3"],
+            Action::Charts => &["f4"],
+            Action::CycleHeatmapView => &["tab"],
+            Action::CloseOverlay => &["esc"],
+            Action::ReplayPause => &["space"],
+            Action::ReplayFaster => &["+", "="],
+            Action::ReplaySlower => &["-"],
+            Action::SeekBack => &["left"],
+            Action::SeekForward => &["right"],
+            Action::SeekBackLong => &["ctrl-left", "pageup"],
+            Action::SeekForwardLong => &["ctrl-right", "pagedown"],
+            Action::ReplayStart => &["home"],
+            Action::ReplayEnd => &["end"],
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::Help => "Show this help",
+            Action::Quit => "End the session and show its summary",
+            Action::Pause => "Pause the clock until you type again",
+            Action::ToggleWidth => "Toggle the width of the text",
+            Action::ScrollUp => "Scroll up a line",
+            Action::ScrollDown => "Scroll down a line",
+            Action::ScrollPageUp => "Scroll up 10 lines",
+            Action::ScrollPageDown => "Scroll down 10 lines",
+            Action::FollowCursor => "Scroll back to the cursor and clear the search",
+            Action::Search => "Search the book",
+            Action::NextMatch => "Scroll to the next match of the search",
+            Action::PreviousMatch => "Scroll to the previous match of the search",
+            Action::StartHere => "Start practicing from the place scrolled to",
+            Action::AddBookmark => "Bookmark the place scrolled to, or the cursor",
+            Action::Bookmarks => "Show the bookmarks",
+            Action::Profiles => "Compare the profiles on this book and switch between them",
+            Action::GoToBookmark => "Scroll to the selected bookmark",
+            Action::DeleteBookmark => "Delete the selected bookmark",
+            Action::Heatmap => "Show the key heatmap",
+            Action::Transitions => "Show the slowest bigrams and trigrams",
+            Action::Charts => "Show the history charts",
+            Action::CycleHeatmapView => "Change the statistic of the heatmap",
+            Action::CloseOverlay => "Close the panel",
+            Action::ReplayPause => "Pause or resume the replay",
+            Action::ReplayFaster => "Replay twice as fast",
+            Action::ReplaySlower => "Replay half as fast",
+            Action::SeekBack => "Go back 5 seconds",
+            Action::SeekForward => "Go forward 5 seconds",
+            Action::SeekBackLong => "Go back a minute",
+            Action::SeekForwardLong => "Go forward a minute",
+            Action::ReplayStart => "Go to the start of the replay",
+            Action::ReplayEnd => "Go to the end of the replay",
+        }
+    }
+}
+
+/// Key with its modifiers, written in the config as e.g. `ctrl-f` or `f1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Key {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl Key {
+    /// Whether a key event is this key. Shift is ignored for characters,
+    /// since it is already part of the character typed.
+    pub fn matches(&self, event: &KeyEvent) -> bool {
+        let modifiers = match event.code {
+            KeyCode::Char(_) => event.modifiers - KeyModifiers::SHIFT,
+            _ => event.modifiers,
+        };
+        let code = match event.code {
+            KeyCode::BackTab => KeyCode::Tab,
+            code => code,
+        };
+        code == self.code && modifiers == self.modifiers
+    }
+
+    /// Whether pressing the key types a character.
+    fn is_typing(&self) -> bool {
+        matches!(self.code, KeyCode::Char(_)) && (self.modifiers - KeyModifiers::SHIFT).is_empty()
+    }
+}
+
+const KEY_NAMES: [(&str, KeyCode); 14] = [
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+    ("pageup", KeyCode::PageUp),
+    ("pagedown", KeyCode::PageDown),
+    ("esc", KeyCode::Esc),
+    ("tab", KeyCode::Tab),
+    ("enter", KeyCode::Enter),
+    ("backspace", KeyCode::Backspace),
+    ("delete", KeyCode::Delete),
+    ("space", KeyCode::Char(' ')),
+];
+
+impl FromStr for Key {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = s;
+        loop {
+            let lower = rest.to_ascii_lowercase();
+            let (modifier, len) = if lower.starts_with("ctrl-") {
+                (KeyModifiers::CONTROL, 5)
+            } else if lower.starts_with("alt-") {
+                (KeyModifiers::ALT, 4)
+            } else {
+                break;
+            };
+            if rest.len() == len {
+                break;
+            }
+            modifiers |= modifier;
+            rest = &rest[len..];
+        }
+        let lower = rest.to_ascii_lowercase();
+        let code = if let Some(&(_, code)) = KEY_NAMES.iter().find(|(name, _)| *name == lower) {
+            code
+        } else if let Some(n) = lower.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+            KeyCode::F(n)
+        } else {
+            let mut chars = rest.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) if !c.is_whitespace() && !c.is_control() => {
+                    // Terminals report control combinations with lowercase letters.
+                    if modifiers.contains(KeyModifiers::CONTROL) {
+                        KeyCode::Char(c.to_ascii_lowercase())
+                    } else {
+                        KeyCode::Char(c)
+                    }
+                }
+                _ => return Err(format!("Invalid key name: {}", s)),
+            }
+        };
+        Ok(Key { code, modifiers })
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt-")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) if self.modifiers.is_empty() => write!(f, "{}", c),
+            KeyCode::Char(c) => write!(f, "{}", c.to_ascii_uppercase()),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            KeyCode::PageUp => write!(f, "PageUp"),
+            KeyCode::PageDown => write!(f, "PageDown"),
+            code => write!(f, "{:?}", code),
+        }
+    }
+}
+
+/// Keys bound to each action.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keymap {
+    bindings: Vec<(Key, Action)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Keymap {
+            bindings: Action::ALL
+                .iter()
+                .flat_map(|&action| {
+                    action
+                        .default_keys()
+                        .iter()
+                        .map(move |key| (key.parse().unwrap(), action))
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Keymap {
+    /// Builds the keymap from the defaults and the bindings in the config,
+    /// reporting every invalid key name and conflict.
+    pub fn from_config(config: &Config) -> AppResult<Self> {
+        let mut errors = Vec::new();
+        let mut bindings = Vec::new();
+        for action in Action::ALL {
+            match config.keys.get(&action) {
+                Some(names) => {
+                    for name in names {
+                        match name.parse::<Key>() {
+                            Ok(key) => bindings.push((key, action)),
+                            Err(e) => errors.push(e),
+                        }
+                    }
+                }
+                None => bindings.extend(
+                    action
+                        .default_keys()
+                        .iter()
+                        .map(|key| (key.parse().unwrap(), action)),
+                ),
+            }
+        }
+
+        for (i, &(key, action)) in bindings.iter().enumerate() {
+            if key.is_typing() && matches!(action.context(), Context::Global | Context::Text) {
+                errors.push(format!(
+                    "{} is bound to {}, which stops it being typed",
+                    key,
+                    action_name(action)
+                ));
+            }
+            for &(other_key, other) in &bindings[..i] {
+                if key == other_key && other != action && action.overlaps(other) {
+                    errors.push(format!(
+                        "{} is bound to both {} and {}",
+                        key,
+                        action_name(other),
+                        action_name(action)
+                    ));
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(Keymap { bindings })
+        } else {
+            Err(format!(
+                "Invalid key bindings in the config file:\n{}",
+                errors.join("\n")
+            )
+            .into())
+        }
+    }
+
+    /// Action bound to a key event in any of the active contexts.
+    pub fn action(&self, event: &KeyEvent, active: &[Context]) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(key, action)| key.matches(event) && active.contains(&action.context()))
+            .map(|&(_, action)| action)
+    }
+
+    /// Keys bound to an action.
+    pub fn keys(&self, action: Action) -> impl Iterator<Item = Key> + '_ {
+        self.bindings
+            .iter()
+            .filter(move |&&(_, a)| a == action)
+            .map(|&(key, _)| key)
+    }
+}
+
+/// Name of an action in the config.
+pub fn action_name(action: Action) -> String {
+    serde_json::to_value(action)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_owned))
+        .unwrap_or_default()
+}
@@ -1,14 +1,75 @@
 /// Application.
 pub mod app;
 
+/// Source of the current time.
+pub mod clock;
+
 /// Terminal events handler.
 pub mod event;
 
 /// Widget renderer.
 pub mod ui;
 
+/// Widget for text being typed.
+pub mod typing_text;
+
 /// Terminal user interface.
 pub mod tui;
 
 /// Event handler.
 pub mod handler;
+
+/// Command line parsing.
+pub mod cli;
+
+/// Practice logs.
+pub mod log;
+
+/// Practice statistics.
+pub mod stats;
+
+/// Export of practice logs.
+pub mod export;
+
+/// Replay of recorded typing.
+pub mod replay;
+
+/// Typing analysis.
+pub mod analysis;
+
+/// Drills of weak keys and bigrams.
+pub mod drill;
+
+/// Races against earlier attempts.
+pub mod ghost;
+
+/// Past outcomes at a position in a book.
+pub mod inspect;
+
+/// Search for text in a book.
+pub mod search;
+
+/// Progress through books.
+pub mod progress;
+
+/// Profiles of people sharing a library.
+pub mod profile;
+
+/// User settings.
+pub mod config;
+
+/// Color themes.
+pub mod theme;
+
+/// Key bindings.
+pub mod keymap;
+
+/// Recording of terminal events, to run the app again.
+pub mod record;
+
+/// Running the app without a terminal.
+#[cfg(feature = "harness")]
+pub mod harness;
+
+/// Daily goals and streaks.
+pub mod goals;
@@ -0,0 +1,303 @@
+use crate::app::AppResult;
+use chrono::{serde::ts_nanoseconds, DateTime, Duration, Utc};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{fs, fs::File, io::Read, io::Seek, path::PathBuf};
+
+/// Directory holding the book texts and their logs, `~/.booktyping`.
+pub fn library_dir() -> PathBuf {
+    dirs::home_dir().unwrap().join(".booktyping")
+}
+
+/// Logs of one profile in a library.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Logs {
+    /// Directory holding the book texts and the logs of every profile.
+    pub library: PathBuf,
+    /// Profile whose logs are read and written, or `None` for the default
+    /// profile.
+    pub profile: Option<String>,
+}
+
+impl Logs {
+    /// Logs of a profile in a library.
+    pub fn new(library: PathBuf, profile: Option<String>) -> Self {
+        Logs { library, profile }
+    }
+
+    /// Logs of another profile in the same library.
+    pub fn with_profile(&self, profile: Option<String>) -> Self {
+        Logs {
+            library: self.library.clone(),
+            profile,
+        }
+    }
+
+    /// Directory holding the logs of the profile, which is the library
+    /// itself for the default profile, so that logs from before profiles are
+    /// kept, and `profiles/{name}` in it for the others.
+    pub fn profile_dir(&self) -> PathBuf {
+        match &self.profile {
+            Some(name) => self.library.join("profiles").join(name),
+            None => self.library.clone(),
+        }
+    }
+
+    /// Directory holding the logs of a book for the profile.
+    pub fn book_dir(&self, book_title: &str) -> PathBuf {
+        self.profile_dir().join(book_title)
+    }
+
+    /// Path of the text of a book.
+    pub fn book_path(&self, book_title: &str) -> PathBuf {
+        self.library.join(format!("{}.txt", book_title))
+    }
+
+    /// Titles of the books in the library, in alphabetical order.
+    pub fn book_titles(&self) -> AppResult<Vec<String>> {
+        let entries = match fs::read_dir(&self.library) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut titles = entries
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                (path.extension()? == "txt").then(|| path.file_stem()?.to_str().map(str::to_owned))?
+            })
+            .collect::<Vec<_>>();
+        titles.sort();
+        Ok(titles)
+    }
+
+    /// Opens the keypress log, which holds one JSON object per keypress.
+    pub fn open_keypress_log(&self, book_title: &str) -> AppResult<File> {
+        Ok(fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.book_dir(book_title).join("keypresses.json"))?)
+    }
+
+    /// Opens a log which holds a single JSON array, rewritten on every entry.
+    pub fn open_json_log(&self, book_title: &str, file_name: &str) -> AppResult<File> {
+        Ok(fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .read(true)
+            .write(true)
+            .open(self.book_dir(book_title).join(file_name))?)
+    }
+
+    /// Reads a JSON array log without creating it.
+    fn read_log_file<T: DeserializeOwned>(
+        &self,
+        book_title: &str,
+        file_name: &str,
+    ) -> AppResult<Vec<T>> {
+        match fs::read_to_string(self.book_dir(book_title).join(file_name)) {
+            Ok(string) => Ok(serde_json::from_str(&string).unwrap_or(Vec::new())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn read_tests(&self, book_title: &str) -> AppResult<Vec<Test>> {
+        self.read_log_file(book_title, "tests.json")
+    }
+
+    pub fn read_drills(&self, book_title: &str) -> AppResult<Vec<Test>> {
+        self.read_log_file(book_title, "drills.json")
+    }
+
+    pub fn read_timed_tests(&self, book_title: &str) -> AppResult<Vec<TimedTest>> {
+        self.read_log_file(book_title, "timed.json")
+    }
+
+    pub fn read_sessions(&self, book_title: &str) -> AppResult<Vec<Session>> {
+        self.read_log_file(book_title, "sessions.json")
+    }
+
+    pub fn read_positions(&self, book_title: &str) -> AppResult<Vec<Position>> {
+        self.read_log_file(book_title, "positions.json")
+    }
+
+    /// Reads the keypress log, ignoring a truncated final entry.
+    pub fn read_keypresses(&self, book_title: &str) -> AppResult<Vec<KeyPress>> {
+        let string = match fs::read_to_string(self.book_dir(book_title).join("keypresses.json")) {
+            Ok(string) => string,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(serde_json::Deserializer::from_str(&string)
+            .into_iter::<KeyPress>()
+            .map_while(Result::ok)
+            .collect())
+    }
+}
+
+/// Reads the entries of a log opened with [`Logs::open_json_log`].
+pub fn read_log<T: DeserializeOwned>(log: &mut File) -> AppResult<Vec<T>> {
+    let mut string = String::new();
+    log.seek(std::io::SeekFrom::Start(0))?;
+    log.read_to_string(&mut string)?;
+    Ok(serde_json::from_str(&string).unwrap_or(Vec::new()))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyPress {
+    pub correct: bool,
+    pub key: char,
+    /// Character the key should have been, missing from older logs.
+    #[serde(default)]
+    pub expected: Option<char>,
+    #[serde(with = "ts_nanoseconds")]
+    pub time: DateTime<Utc>,
+}
+
+impl KeyPress {
+    /// Character the key should have been, when it can be known.
+    pub fn expected(&self) -> Option<char> {
+        match self.expected {
+            Some(expected) => Some(expected),
+            None if self.correct => Some(self.key),
+            None => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Test {
+    pub succeeded: bool,
+    pub start_index: usize,
+    pub end_index: usize,
+    #[serde(with = "ts_nanoseconds")]
+    pub started: DateTime<Utc>,
+    #[serde(with = "ts_nanoseconds")]
+    pub completed: DateTime<Utc>,
+    /// Words per minute, counting 5 characters as a word.
+    #[serde(default)]
+    pub wpm: Option<f64>,
+    /// Fraction of the keypresses in the sample which were correct.
+    #[serde(default)]
+    pub accuracy: Option<f64>,
+    /// Breaks taken during the sample, which don't count toward its duration.
+    #[serde(default)]
+    pub pauses: Vec<Pause>,
+    /// Character typed instead of the one at `end_index`, if the sample failed.
+    #[serde(default)]
+    pub mistyped: Option<char>,
+}
+
+impl Test {
+    /// Number of characters typed correctly.
+    pub fn length(&self) -> usize {
+        self.end_index - self.start_index
+    }
+
+    /// Time spent on the sample, leaving out its pauses.
+    pub fn duration(&self) -> Duration {
+        self.completed - self.started - paused_time(&self.pauses, self.started, self.completed)
+    }
+}
+
+/// Break from typing, taken with the pause key or by leaving the keyboard idle.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Pause {
+    #[serde(with = "ts_nanoseconds")]
+    pub started: DateTime<Utc>,
+    #[serde(with = "ts_nanoseconds")]
+    pub ended: DateTime<Utc>,
+}
+
+/// Time between `from` and `to` which was spent paused.
+pub fn paused_time(pauses: &[Pause], from: DateTime<Utc>, to: DateTime<Utc>) -> Duration {
+    pauses
+        .iter()
+        .map(|p| DateTime::min(p.ended, to) - DateTime::max(p.started, from))
+        .filter(|&d| d > Duration::zero())
+        .sum()
+}
+
+/// Result of a timed test.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimedTest {
+    pub start_index: usize,
+    pub end_index: usize,
+    pub keypresses: usize,
+    pub mistakes: usize,
+    /// Words per minute counting every keypress.
+    pub gross_wpm: f64,
+    /// Gross words per minute less one word per mistake per minute.
+    pub net_wpm: f64,
+    pub accuracy: f64,
+    #[serde(with = "ts_nanoseconds")]
+    pub started: DateTime<Utc>,
+    #[serde(with = "ts_nanoseconds")]
+    pub completed: DateTime<Utc>,
+    /// Breaks taken during the test, which didn't use up its time.
+    #[serde(default)]
+    pub pauses: Vec<Pause>,
+}
+
+impl TimedTest {
+    /// Time spent typing, leaving out the pauses.
+    pub fn duration(&self) -> Duration {
+        self.completed - self.started - paused_time(&self.pauses, self.started, self.completed)
+    }
+}
+
+/// Summary of one run of the typing interface.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    #[serde(with = "ts_nanoseconds")]
+    pub started: DateTime<Utc>,
+    #[serde(with = "ts_nanoseconds")]
+    pub ended: DateTime<Utc>,
+    pub samples: usize,
+    pub samples_passed: usize,
+    pub chars_advanced: usize,
+    pub practice_seconds: i64,
+    pub average_wpm: Option<f64>,
+    pub best_wpm: Option<f64>,
+    pub accuracy: Option<f64>,
+    /// Characters missed most often, with the number of times each was missed.
+    pub most_missed: Vec<(char, usize)>,
+}
+
+/// Place in the book chosen to practice from, which replaces the furthest
+/// point reached before it was chosen.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Position {
+    pub index: usize,
+    #[serde(with = "ts_nanoseconds")]
+    pub chosen: DateTime<Utc>,
+}
+
+/// Named place in a book.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub name: String,
+    pub index: usize,
+    #[serde(with = "ts_nanoseconds")]
+    pub created: DateTime<Utc>,
+}
+
+/// Position in the book where the next sample starts: the furthest point
+/// reached by a successful sample or a timed test since the last position
+/// was chosen, or that position if nothing has gone past it.
+pub fn book_position(tests: &[Test], timed_tests: &[TimedTest], positions: &[Position]) -> usize {
+    let (mut start_index, since) = match positions.last() {
+        Some(position) => (position.index, Some(position.chosen)),
+        None => (0, None),
+    };
+    for t in tests {
+        if t.succeeded && t.end_index > start_index && since.is_none_or(|s| t.completed > s) {
+            start_index = t.end_index;
+        }
+    }
+    for t in timed_tests {
+        if t.end_index > start_index && since.is_none_or(|s| t.completed > s) {
+            start_index = t.end_index;
+        }
+    }
+    start_index
+}
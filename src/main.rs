@@ -1,43 +1,110 @@
-use booktyping::app::{App, AppResult};
-use booktyping::event::{Event, EventHandler};
-use booktyping::handler::handle_key_events;
+use booktyping::app::{App, AppResult, Mode};
+use booktyping::cli::{self, Command};
+use booktyping::config::Config;
+use booktyping::event::EventHandler;
+use booktyping::handler::handle_event;
+use booktyping::record::{Header, Recorder};
+use booktyping::log::{self, Logs};
+use booktyping::{export, profile, progress, replay, stats};
 use booktyping::tui::Tui;
 use ratatui::backend::CrosstermBackend;
 use ratatui::Terminal;
-use std::{env, io};
-fn main() -> AppResult<()> {
+use std::{env, io, process};
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
+
+fn run() -> AppResult<()> {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let (command_args, profile_name) = match cli::split_profile(&args) {
+        Ok(split) => split,
+        Err(message) => {
+            println!("{}", message);
+            return Ok(());
+        }
+    };
+    let profile = match profile_name.as_deref().map(profile::from_name) {
+        Some(Ok(profile)) => profile,
+        Some(Err(message)) => {
+            println!("{}", message);
+            return Ok(());
+        }
+        None => None,
+    };
+    let logs = Logs::new(log::library_dir(), profile);
+    let (book_title, mode, record) = match cli::parse(&command_args) {
+        Ok(Command::Practice {
+            book_title,
+            mode,
+            record,
+        }) => (book_title, mode, record),
+        Ok(Command::Stats { book_title, json }) => return stats::run(&logs, &book_title, json),
+        Ok(Command::Export {
+            book_title,
+            options,
+        }) => return export::run(&logs, &book_title, &options),
+        Ok(Command::Profiles { book_title }) => return profile::run(&logs, book_title.as_deref()),
+        Ok(Command::List) => {
+            progress::print_book_list(&logs)?;
+            println!("\n{}", cli::USAGE);
+            return Ok(());
+        }
+        Err(message) => {
+            println!("{}", message);
+            if message == cli::USAGE {
+                println!("Please provide the name of a book in the ~/.booktyping directory");
+            }
+            return Ok(());
+        }
+    };
+
     let backend = CrosstermBackend::new(io::stderr());
     let terminal = Terminal::new(backend)?;
 
-    let book_title = if let Some(arg_1) = env::args().collect::<Vec<_>>().get(1) {
-        arg_1.clone()
-    } else {
-        println!("Please provide the name of a book in the ~/.booktyping directory");
-        return Ok(());
+    let mut recorder = match record {
+        Some(path) => {
+            let size = terminal.size()?;
+            let header = Header::new(
+                &logs,
+                cli::split_record(&args)?.0,
+                &book_title,
+                (size.width, size.height),
+                chrono::Utc::now(),
+            )?;
+            Some(Recorder::create(&path, &header)?)
+        }
+        None => None,
     };
 
-    let mut app = App::new(&book_title, terminal.size()?.width)?;
+    let config = Config::load(&logs)?;
+    let mut app = App::new(logs, &book_title, terminal.size()?.width, mode, config)?;
 
-    let events = EventHandler::new(250);
+    let events = EventHandler::new(match mode {
+        Mode::Replay(_) => replay::TICK_RATE_MS,
+        _ => 100,
+    });
     let mut tui = Tui::new(terminal, events);
 
-    tui.init()?;
+    // Ask who is practicing, unless they said.
+    if profile_name.is_none() && profile::profiles(&app.logs)?.len() > 1 {
+        app.toggle_profiles()?;
+    }
+
+    tui.init(app.config.mouse)?;
     tui.draw(&mut app)?; //Draw first frame
 
     // Start the main loop.
     while app.running {
         // Handle events.
-        match tui.events.next()? {
-            Event::Key(key_event) => {
-                handle_key_events(key_event, &mut app)?;
-                tui.draw(&mut app)?;
-            }
-            Event::Resize(width, _) => {
-                app.terminal_width = width;
-                app.generate_lines();
-                tui.draw(&mut app)?;
-            }
+        let event = tui.events.next()?;
+        if let Some(recorder) = &mut recorder {
+            recorder.record(app.clock.now(), event)?;
         }
+        handle_event(event, &mut app)?;
+        tui.draw(&mut app)?;
     }
 
     // Exit the user interface.
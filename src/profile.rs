@@ -0,0 +1,216 @@
+use crate::app::AppResult;
+use crate::log::Logs;
+use crate::progress::{format_duration, Progress};
+use chrono::{DateTime, Duration, Local, Utc};
+use std::fs;
+
+/// Name of the profile whose logs are kept directly in the library.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Longest name a profile can have.
+const MAX_NAME_LEN: usize = 32;
+
+/// Profile with a given name, or `None` for the default profile, checking
+/// that the name can be used for its directory.
+pub fn from_name(name: &str) -> Result<Option<String>, String> {
+    let name = name.trim();
+    if name == DEFAULT_PROFILE {
+        return Ok(None);
+    }
+    let valid = !name.is_empty()
+        && name.len() <= MAX_NAME_LEN
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, ' ' | '-' | '_' | '.'));
+    match valid {
+        true => Ok(Some(name.to_owned())),
+        false => Err(format!(
+            "Invalid profile name {:?}, use up to {} letters, digits, spaces, '-', '_' and '.'",
+            name, MAX_NAME_LEN
+        )),
+    }
+}
+
+/// Name of a profile, as given to [`from_name`].
+pub fn name(profile: &Option<String>) -> &str {
+    profile.as_deref().unwrap_or(DEFAULT_PROFILE)
+}
+
+/// Profiles in the library, starting with the default one and then in
+/// alphabetical order.
+pub fn profiles(logs: &Logs) -> AppResult<Vec<Option<String>>> {
+    let mut names = match fs::read_dir(logs.library.join("profiles")) {
+        Ok(entries) => entries
+            .filter_map(|entry| {
+                let entry = entry.ok()?;
+                entry.file_type().ok()?.is_dir().then_some(())?;
+                entry.file_name().to_str().map(str::to_owned)
+            })
+            .collect::<Vec<_>>(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(e.into()),
+    };
+    names.sort();
+    Ok(std::iter::once(None)
+        .chain(names.into_iter().map(Some))
+        .collect())
+}
+
+/// Practice of one profile on a book, to compare it with the others.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Standing {
+    pub profile: Option<String>,
+    pub progress_percent: f64,
+    pub samples: usize,
+    pub samples_passed: usize,
+    /// Words per minute over all samples and timed tests.
+    pub wpm: Option<f64>,
+    /// Mean accuracy of the samples and timed tests.
+    pub accuracy: Option<f64>,
+    pub practice_time: Duration,
+    pub last_practiced: Option<DateTime<Utc>>,
+}
+
+impl Standing {
+    /// Reads the logs of a profile for a book.
+    pub fn load(logs: &Logs, book_title: &str) -> AppResult<Self> {
+        let tests = logs.read_tests(book_title)?;
+        let timed_tests = logs.read_timed_tests(book_title)?;
+        let chars: usize = tests.iter().map(|t| t.length()).sum::<usize>()
+            + timed_tests
+                .iter()
+                .map(|t| t.end_index - t.start_index)
+                .sum::<usize>();
+        let practice_time = tests
+            .iter()
+            .map(|t| t.duration())
+            .chain(timed_tests.iter().map(|t| t.duration()))
+            .sum::<Duration>();
+        let minutes = practice_time.num_milliseconds() as f64 / 60_000.0;
+        let accuracies = tests
+            .iter()
+            .filter_map(|t| t.accuracy)
+            .chain(timed_tests.iter().map(|t| t.accuracy))
+            .collect::<Vec<_>>();
+        Ok(Standing {
+            progress_percent: Progress::load(logs, book_title)?.percent(),
+            samples: tests.len(),
+            samples_passed: tests.iter().filter(|t| t.succeeded).count(),
+            wpm: (minutes > 0.0).then(|| chars as f64 / 5.0 / minutes),
+            accuracy: (!accuracies.is_empty())
+                .then(|| accuracies.iter().sum::<f64>() / accuracies.len() as f64),
+            practice_time,
+            last_practiced: tests
+                .iter()
+                .map(|t| t.completed)
+                .chain(timed_tests.iter().map(|t| t.completed))
+                .max(),
+            profile: logs.profile.clone(),
+        })
+    }
+
+    /// Columns of the comparison table.
+    pub fn columns(&self) -> [String; 7] {
+        [
+            name(&self.profile).to_owned(),
+            format!("{:.1}%", self.progress_percent),
+            format!("{}/{}", self.samples_passed, self.samples),
+            self.wpm.map_or("-".to_owned(), |wpm| format!("{:.0}", wpm)),
+            self.accuracy.map_or("-".to_owned(), |accuracy| {
+                format!("{:.1}%", accuracy * 100.0)
+            }),
+            format_duration(self.practice_time),
+            self.last_practiced.map_or("never".to_owned(), |time| {
+                time.with_timezone(&Local).format("%Y-%m-%d").to_string()
+            }),
+        ]
+    }
+}
+
+/// Headings of the columns of the comparison table.
+pub const COLUMNS: [&str; 7] = [
+    "Profile",
+    "Progress",
+    "Passed",
+    "WPM",
+    "Accuracy",
+    "Practice",
+    "Last practiced",
+];
+
+/// Practice of every profile on a book.
+pub fn compare(logs: &Logs, book_title: &str) -> AppResult<Vec<Standing>> {
+    profiles(logs)?
+        .into_iter()
+        .map(|profile| Standing::load(&logs.with_profile(profile), book_title))
+        .collect()
+}
+
+/// Prints the profiles, marking the one of `logs`, or how each of them is
+/// doing on a book.
+pub fn run(logs: &Logs, book_title: Option<&str>) -> AppResult<()> {
+    let Some(book_title) = book_title else {
+        for profile in profiles(logs)? {
+            let marker = if profile == logs.profile { "*" } else { " " };
+            println!("{} {}", marker, name(&profile));
+        }
+        return Ok(());
+    };
+    let rows = compare(logs, book_title)?
+        .iter()
+        .map(Standing::columns)
+        .collect::<Vec<_>>();
+    let widths = COLUMNS.map(str::len);
+    let widths: [usize; 7] = std::array::from_fn(|i| {
+        rows.iter()
+            .map(|row| row[i].len())
+            .fold(widths[i], usize::max)
+    });
+    for row in std::iter::once(COLUMNS.map(str::to_owned)).chain(rows) {
+        let cells = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{:width$}", cell, width = width))
+            .collect::<Vec<_>>();
+        println!("{}", cells.join("  ").trim_end());
+    }
+    Ok(())
+}
+
+/// Profiles compared on the book being practiced, to pick one to switch to
+/// or to type the name of a new one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Picker {
+    pub standings: Vec<Standing>,
+    /// Index into `standings` of the selected profile.
+    pub selected: usize,
+    /// Name of a new profile, as it is typed.
+    pub name: String,
+    /// Why the last name typed couldn't be used.
+    pub error: Option<String>,
+}
+
+impl Picker {
+    /// Compares the profiles on a book, selecting the one of `logs`.
+    pub fn load(logs: &Logs, book_title: &str) -> AppResult<Self> {
+        let standings = compare(logs, book_title)?;
+        Ok(Picker {
+            selected: standings
+                .iter()
+                .position(|standing| standing.profile == logs.profile)
+                .unwrap_or(0),
+            standings,
+            name: String::new(),
+            error: None,
+        })
+    }
+
+    /// Profile to switch to, which is the one typed if there is one.
+    pub fn chosen(&self) -> Result<Option<String>, String> {
+        match self.name.trim() {
+            "" => Ok(self.standings[self.selected].profile.clone()),
+            name => from_name(name),
+        }
+    }
+}
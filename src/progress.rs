@@ -0,0 +1,145 @@
+use crate::app::{App, AppResult};
+use crate::log::{self, Logs, Position, Test, TimedTest};
+use chrono::Duration;
+use regex::Regex;
+
+/// Number of recent samples the typing throughput is measured over.
+const THROUGHPUT_SAMPLES: usize = 100;
+
+/// Progress through a book.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Progress {
+    pub position: usize,
+    pub book_len: usize,
+    pub words_remaining: usize,
+    pub chapter: Option<String>,
+    /// Characters advanced per minute of recent practice.
+    pub chars_per_minute: Option<f64>,
+}
+
+impl Progress {
+    pub fn new(
+        book_text: &str,
+        chapters: &[(usize, String)],
+        tests: &[Test],
+        timed_tests: &[TimedTest],
+        positions: &[Position],
+    ) -> Self {
+        let position = log::book_position(tests, timed_tests, positions);
+
+        let recent = &tests[tests.len().saturating_sub(THROUGHPUT_SAMPLES)..];
+        let advanced = recent
+            .iter()
+            .filter(|t| t.succeeded)
+            .map(Test::length)
+            .sum::<usize>();
+        let minutes = recent
+            .iter()
+            .map(|t| t.duration().num_milliseconds())
+            .sum::<i64>() as f64
+            / 60_000.0;
+
+        Progress {
+            position,
+            book_len: book_text.len(),
+            words_remaining: book_text
+                .get(position..)
+                .unwrap_or_default()
+                .split_whitespace()
+                .count(),
+            chapter: chapters
+                .iter()
+                .take_while(|&&(start, _)| start <= position)
+                .last()
+                .map(|(_, chapter)| chapter.clone()),
+            chars_per_minute: (advanced > 0 && minutes > 0.0).then(|| advanced as f64 / minutes),
+        }
+    }
+
+    /// Computes the progress through a book from its logs.
+    pub fn load(logs: &Logs, book_title: &str) -> AppResult<Self> {
+        let book_text = App::load_book(logs, book_title)?;
+        Ok(Progress::new(
+            &book_text,
+            &find_chapters(&book_text),
+            &logs.read_tests(book_title)?,
+            &logs.read_timed_tests(book_title)?,
+            &logs.read_positions(book_title)?,
+        ))
+    }
+
+    pub fn percent(&self) -> f64 {
+        self.position as f64 / usize::max(self.book_len, 1) as f64 * 100.0
+    }
+
+    pub fn chars_remaining(&self) -> usize {
+        self.book_len.saturating_sub(self.position)
+    }
+
+    /// Practice time needed to finish the book at the recent throughput.
+    pub fn time_remaining(&self) -> Option<Duration> {
+        self.chars_per_minute
+            .map(|cpm| Duration::seconds((self.chars_remaining() as f64 / cpm * 60.0) as i64))
+    }
+}
+
+/// Finds the chapter headings in a book, with the index each one starts at.
+/// "Chapter" following a lowercase word or a comma is taken to be mid-sentence
+/// rather than a heading.
+pub fn find_chapters(book_text: &str) -> Vec<(usize, String)> {
+    Regex::new(r"\b(?:CHAPTER|Chapter)\s+(?:[0-9]+|[IVXLCDM]+|[ivxlcdm]+|[A-Z][a-z]+|[A-Z]+)\b")
+        .unwrap()
+        .find_iter(book_text)
+        .filter(|m| {
+            !book_text[..m.start()]
+                .trim_end()
+                .ends_with(|c: char| c.is_lowercase() || c == ',' || c == ';')
+        })
+        .map(|m| (m.start(), m.as_str().to_owned()))
+        .collect()
+}
+
+/// Formats a duration as hours and minutes.
+pub fn format_duration(duration: Duration) -> String {
+    let minutes = duration.num_minutes();
+    if minutes < 60 {
+        format!("{}m", minutes)
+    } else {
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    }
+}
+
+/// Draws a text progress bar `width` characters wide.
+pub fn progress_bar(percent: f64, width: usize) -> String {
+    let filled = ((percent / 100.0 * width as f64).round() as usize).min(width);
+    format!("[{}{}]", "#".repeat(filled), "-".repeat(width - filled))
+}
+
+/// Prints every book in the library with its progress.
+pub fn print_book_list(logs: &Logs) -> AppResult<()> {
+    let titles = logs.book_titles()?;
+    if titles.is_empty() {
+        println!("No books found in {}", logs.library.display());
+    }
+    for title in titles {
+        let progress = Progress::load(logs, &title)?;
+        println!(
+            "{} {} {:5.1}%  {}",
+            progress_bar(progress.percent(), 20),
+            title,
+            progress.percent(),
+            progress.chapter.as_deref().unwrap_or_default(),
+        );
+        println!(
+            "{:24}{} characters, {} words left{}",
+            "",
+            progress.chars_remaining(),
+            progress.words_remaining,
+            progress
+                .time_remaining()
+                .map(|t| format!(", about {} of practice", format_duration(t)))
+                .unwrap_or_default(),
+        );
+    }
+    Ok(())
+}
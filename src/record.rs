@@ -0,0 +1,131 @@
+use crate::app::AppResult;
+use crate::event::Event;
+use crate::log::Logs;
+use chrono::{serde::ts_nanoseconds, DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+
+/// Start of a recording, with everything needed to run the app again as it
+/// was when the recording started.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Header {
+    /// Command line arguments, without `--record`.
+    pub args: Vec<String>,
+    pub width: u16,
+    pub height: u16,
+    #[serde(with = "ts_nanoseconds")]
+    pub started: DateTime<Utc>,
+    /// Contents of the book text, its logs and the config, by their paths in
+    /// the library directory.
+    pub files: BTreeMap<String, String>,
+}
+
+impl Header {
+    /// Copies the files of a book from the library, with the logs and config
+    /// of a profile.
+    pub fn new(
+        logs: &Logs,
+        args: Vec<String>,
+        book_title: &str,
+        (width, height): (u16, u16),
+        started: DateTime<Utc>,
+    ) -> AppResult<Self> {
+        let library = &logs.library;
+        let mut paths = vec![
+            logs.book_path(book_title),
+            library.join("config.json"),
+            logs.profile_dir().join("config.json"),
+        ];
+        if let Ok(entries) = fs::read_dir(logs.book_dir(book_title)) {
+            for entry in entries {
+                paths.push(entry?.path());
+            }
+        }
+        let mut files = BTreeMap::new();
+        for path in paths {
+            if let (Ok(relative), Ok(contents)) =
+                (path.strip_prefix(library), fs::read_to_string(&path))
+            {
+                files.insert(relative.to_string_lossy().into_owned(), contents);
+            }
+        }
+        Ok(Header {
+            args,
+            width,
+            height,
+            started,
+            files,
+        })
+    }
+
+    /// Writes the files into a library directory.
+    pub fn restore_files(&self, library: &Path) -> AppResult<()> {
+        for (path, contents) in &self.files {
+            let path = library.join(path);
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            fs::write(path, contents)?;
+        }
+        Ok(())
+    }
+}
+
+/// Event received by the app, with the time it was received.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    #[serde(with = "ts_nanoseconds")]
+    pub time: DateTime<Utc>,
+    pub event: Event,
+}
+
+/// Writes the events of a run to a file, one JSON object per line after the
+/// header. Ticks aren't recorded, since they are sent at a fixed rate.
+#[derive(Debug)]
+pub struct Recorder {
+    file: File,
+}
+
+impl Recorder {
+    pub fn create(path: &Path, header: &Header) -> AppResult<Self> {
+        let mut file = File::create(path)?;
+        serde_json::to_writer(&mut file, header)?;
+        writeln!(file)?;
+        Ok(Recorder { file })
+    }
+
+    pub fn record(&mut self, time: DateTime<Utc>, event: Event) -> AppResult<()> {
+        if event != Event::Tick {
+            serde_json::to_writer(&mut self.file, &Entry { time, event })?;
+            writeln!(self.file)?;
+        }
+        Ok(())
+    }
+}
+
+/// Events of a run read back from a file written by a [`Recorder`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recording {
+    pub header: Header,
+    pub entries: Vec<Entry>,
+}
+
+impl Recording {
+    /// Reads a recording, ignoring a truncated final entry.
+    pub fn load(path: &Path) -> AppResult<Self> {
+        let string = fs::read_to_string(path)?;
+        let mut values =
+            serde_json::Deserializer::from_str(&string).into_iter::<serde_json::Value>();
+        let header = match values.next() {
+            Some(value) => serde_json::from_value(value?)?,
+            None => return Err(format!("Empty recording {}", path.display()).into()),
+        };
+        let entries = values
+            .map_while(|value| serde_json::from_value(value.ok()?).ok())
+            .collect();
+        Ok(Recording { header, entries })
+    }
+}
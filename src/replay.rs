@@ -0,0 +1,192 @@
+use crate::analysis;
+use crate::app::AppResult;
+use crate::log::Logs;
+use chrono::{DateTime, Duration, Local, Utc};
+
+/// Milliseconds between ticks while replaying, short enough for smooth typing.
+pub const TICK_RATE_MS: u64 = 25;
+/// Longest gap between keypresses which is replayed in full. Longer gaps are
+/// breaks and are cut short.
+const MAX_GAP_MS: i64 = 5000;
+/// Time the text is shown before the first keypress.
+const LEAD_IN_MS: i64 = 1000;
+/// Time skipped by the seek keys.
+pub const SEEK_SECONDS: i64 = 5;
+pub const LONG_SEEK_SECONDS: i64 = 60;
+const MIN_SPEED: f64 = 0.25;
+const MAX_SPEED: f64 = 16.0;
+
+/// Time window of the keypress log to replay, and how fast to replay it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Window {
+    /// Start of the window, or the start of the last session if neither end is given.
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    /// Multiple of real time.
+    pub speed: f64,
+}
+
+/// State of the typing after a keypress.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frame {
+    /// Time into the replay at which the keypress is shown.
+    pub at: Duration,
+    /// Time the keypress was recorded.
+    pub time: DateTime<Utc>,
+    pub sample_start_index: usize,
+    pub sample_len: usize,
+    pub cur_char: usize,
+}
+
+/// Replay of recorded keypresses.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replay {
+    /// State before the first keypress, then after each one.
+    frames: Vec<Frame>,
+    /// Time into the replay.
+    pub position: Duration,
+    pub speed: f64,
+    pub paused: bool,
+    /// When the position was last advanced.
+    last_update: DateTime<Utc>,
+}
+
+impl Replay {
+    /// Loads the keypresses of a book recorded in a window.
+    pub fn load(
+        logs: &Logs,
+        book_title: &str,
+        window: Window,
+        now: DateTime<Utc>,
+    ) -> AppResult<Self> {
+        let (from, to) = match (window.from, window.to) {
+            (None, None) => match logs.read_sessions(book_title)?.last() {
+                Some(session) => (Some(session.started), Some(session.ended)),
+                None => (None, None),
+            },
+            range => range,
+        };
+        let keypresses = logs.read_keypresses(book_title)?;
+        let tests = logs.read_tests(book_title)?;
+        let drills = logs.read_drills(book_title)?;
+        let timed_tests = logs.read_timed_tests(book_title)?;
+        let placements =
+            analysis::keypress_placements(&keypresses, &tests, &drills, &timed_tests);
+
+        let mut frames: Vec<Frame> = Vec::new();
+        for (k, placement) in keypresses.iter().zip(placements) {
+            if from.is_some_and(|from| k.time < from) || to.is_some_and(|to| k.time > to) {
+                continue;
+            }
+            let Some(p) = placement else {
+                continue;
+            };
+            let sample_start_index = p.start_index;
+            let typed = p.index - p.start_index;
+            let cur_char = if k.correct { typed + 1 } else { typed };
+            let sample_len = usize::max(p.end_index - p.start_index, cur_char);
+            let at = match frames.last() {
+                Some(last) => {
+                    last.at
+                        + Duration::milliseconds(
+                            (k.time - last.time).num_milliseconds().clamp(0, MAX_GAP_MS),
+                        )
+                }
+                None => {
+                    frames.push(Frame {
+                        at: Duration::zero(),
+                        time: k.time,
+                        sample_start_index,
+                        sample_len,
+                        cur_char: typed,
+                    });
+                    Duration::milliseconds(LEAD_IN_MS)
+                }
+            };
+            frames.push(Frame {
+                at,
+                time: k.time,
+                sample_start_index,
+                sample_len,
+                cur_char,
+            });
+        }
+        if frames.is_empty() {
+            return Err(match (from, to) {
+                (None, None) => "No keypresses recorded yet to replay".into(),
+                _ => format!(
+                    "No keypresses recorded between {} and {}",
+                    from.map_or("the start of the log".to_owned(), format_time),
+                    to.map_or("now".to_owned(), format_time)
+                )
+                .into(),
+            });
+        }
+        Ok(Replay {
+            frames,
+            position: Duration::zero(),
+            speed: window.speed,
+            paused: false,
+            last_update: now,
+        })
+    }
+
+    /// Advances the replay by the time passed since it was last advanced.
+    pub fn update(&mut self, now: DateTime<Utc>) {
+        if !self.paused {
+            let elapsed = (now - self.last_update).num_milliseconds() as f64 * self.speed;
+            self.seek(Duration::milliseconds(elapsed as i64));
+        }
+        self.last_update = now;
+    }
+
+    /// Moves the replay forward or, with a negative offset, backward.
+    pub fn seek(&mut self, offset: Duration) {
+        self.position = (self.position + offset).clamp(Duration::zero(), self.length());
+    }
+
+    pub fn restart(&mut self) {
+        self.position = Duration::zero();
+    }
+
+    pub fn skip_to_end(&mut self) {
+        self.position = self.length();
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        if !self.paused && self.finished() {
+            self.restart();
+        }
+    }
+
+    pub fn faster(&mut self) {
+        self.speed = f64::min(self.speed * 2.0, MAX_SPEED);
+    }
+
+    pub fn slower(&mut self) {
+        self.speed = f64::max(self.speed / 2.0, MIN_SPEED);
+    }
+
+    /// Time from the first keypress to the last, with breaks cut short.
+    pub fn length(&self) -> Duration {
+        self.frames.last().unwrap().at
+    }
+
+    pub fn finished(&self) -> bool {
+        self.position >= self.length()
+    }
+
+    /// State of the typing at the current position.
+    pub fn frame(&self) -> &Frame {
+        let shown = self.frames.partition_point(|f| f.at <= self.position);
+        &self.frames[shown.saturating_sub(1)]
+    }
+}
+
+/// Formats a time as a local date and time.
+pub fn format_time(time: DateTime<Utc>) -> String {
+    time.with_timezone(&Local)
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string()
+}
@@ -0,0 +1,59 @@
+/// Search for text in a book, with the matches found so far.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Search {
+    pub query: String,
+    /// Whether the query is still being typed into the prompt.
+    pub editing: bool,
+    /// Start indices of the matches in the book, in order.
+    pub matches: Vec<usize>,
+    /// Index into `matches` of the match scrolled to.
+    pub current: usize,
+}
+
+impl Search {
+    /// Starts a search with an empty prompt.
+    pub fn new() -> Self {
+        Search {
+            editing: true,
+            ..Default::default()
+        }
+    }
+
+    /// Finds the matches of the query in the book, ignoring case, and selects
+    /// the first one at or after `from`.
+    pub fn find(&mut self, book_text: &str, from: usize) {
+        self.matches.clear();
+        self.current = 0;
+        if self.query.is_empty() {
+            return;
+        }
+        // The book text is ASCII, so lowercasing keeps the indices.
+        let text = book_text.to_ascii_lowercase();
+        let query = self.query.to_ascii_lowercase();
+        let mut start = 0;
+        while let Some(i) = text[start..].find(&query) {
+            self.matches.push(start + i);
+            start += i + 1;
+        }
+        self.current = self.matches.partition_point(|&i| i < from) % self.matches.len().max(1);
+    }
+
+    /// Start index of the match scrolled to.
+    pub fn current_match(&self) -> Option<usize> {
+        self.matches.get(self.current).copied()
+    }
+
+    /// Selects the next match, wrapping around to the first.
+    pub fn next(&mut self) {
+        if !self.matches.is_empty() {
+            self.current = (self.current + 1) % self.matches.len();
+        }
+    }
+
+    /// Selects the previous match, wrapping around to the last.
+    pub fn previous(&mut self) {
+        if !self.matches.is_empty() {
+            self.current = (self.current + self.matches.len() - 1) % self.matches.len();
+        }
+    }
+}
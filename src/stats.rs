@@ -0,0 +1,364 @@
+use crate::analysis::{self, NgramStats};
+use crate::app::{App, AppResult};
+use crate::config::Config;
+use crate::goals::{self, Activity, GoalReport};
+use crate::log::{self, KeyPress, Logs, Position, Session, Test, TimedTest};
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Number of days and weeks shown in the human readable report.
+const RECENT_DAYS: usize = 14;
+const RECENT_WEEKS: usize = 12;
+const MOST_MISSED: usize = 10;
+const SLOWEST_NGRAMS: usize = 10;
+const RECENT_SESSIONS: usize = 10;
+
+/// Summary of the practice logs of a book.
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub book_title: String,
+    pub book_len: usize,
+    pub position: usize,
+    pub progress_percent: f64,
+    pub practice_seconds: i64,
+    pub samples: usize,
+    pub samples_passed: usize,
+    pub success_rate: Option<f64>,
+    pub timed_tests: usize,
+    pub longest_success: Option<Sample>,
+    pub daily: Vec<Period>,
+    pub weekly: Vec<Period>,
+    pub most_missed: Vec<MissedChar>,
+    pub slowest_bigrams: Vec<NgramStats>,
+    pub slowest_trigrams: Vec<NgramStats>,
+    pub sessions: Vec<Session>,
+    /// Daily goal and streaks across all books.
+    pub goals: Option<GoalReport>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Sample {
+    pub start_index: usize,
+    pub length: usize,
+    pub completed: DateTime<Utc>,
+}
+
+/// Practice over a day or an ISO week, in local time.
+#[derive(Debug, Default, Serialize)]
+pub struct Period {
+    pub period: String,
+    pub samples: usize,
+    pub samples_passed: usize,
+    pub practice_seconds: i64,
+    /// Words per minute over all samples and timed tests in the period.
+    pub wpm: Option<f64>,
+    /// Fraction of the keypresses in the period which were correct.
+    pub accuracy: Option<f64>,
+    #[serde(skip)]
+    chars_typed: usize,
+    #[serde(skip)]
+    keypresses: usize,
+    #[serde(skip)]
+    correct_keypresses: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MissedChar {
+    pub character: char,
+    pub count: usize,
+}
+
+/// Prints the report for a book.
+pub fn run(logs: &Logs, book_title: &str, json: bool) -> AppResult<()> {
+    let report = Report::new(logs, book_title)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print!("{}", report);
+    }
+    Ok(())
+}
+
+impl Report {
+    /// Builds the report from the logs of a book.
+    pub fn new(logs: &Logs, book_title: &str) -> AppResult<Self> {
+        let book_text = App::load_book(logs, book_title)?;
+        let mut report = Report::from_logs(
+            book_title,
+            &book_text,
+            &logs.read_tests(book_title)?,
+            &logs.read_timed_tests(book_title)?,
+            &logs.read_positions(book_title)?,
+            &logs.read_keypresses(book_title)?,
+            logs.read_sessions(book_title)?,
+        );
+        report.goals = Some(GoalReport::new(
+            &Activity::load(logs)?,
+            Config::load(logs)?.daily_goal,
+            goals::local_date(Utc::now()),
+        ));
+        Ok(report)
+    }
+
+    pub fn from_logs(
+        book_title: &str,
+        book_text: &str,
+        tests: &[Test],
+        timed_tests: &[TimedTest],
+        positions: &[Position],
+        keypresses: &[KeyPress],
+        sessions: Vec<Session>,
+    ) -> Self {
+        let position = log::book_position(tests, timed_tests, positions);
+        let samples_passed = tests.iter().filter(|t| t.succeeded).count();
+
+        let longest_success = tests
+            .iter()
+            .filter(|t| t.succeeded)
+            .max_by_key(|t| t.length())
+            .map(|t| Sample {
+                start_index: t.start_index,
+                length: t.length(),
+                completed: t.completed,
+            });
+
+        let mut missed: BTreeMap<char, usize> = BTreeMap::new();
+        for t in tests.iter().filter(|t| !t.succeeded) {
+            if let Some(c) = book_text.chars().nth(t.end_index) {
+                *missed.entry(c).or_default() += 1;
+            }
+        }
+        let mut most_missed = missed
+            .into_iter()
+            .map(|(character, count)| MissedChar { character, count })
+            .collect::<Vec<_>>();
+        most_missed.sort_by_key(|m| std::cmp::Reverse(m.count));
+        most_missed.truncate(MOST_MISSED);
+
+        let day = |time: &DateTime<Utc>| time.with_timezone(&Local).date_naive();
+        let week = |date: NaiveDate| date.format("%G-W%V").to_string();
+        let mut daily: BTreeMap<NaiveDate, Period> = BTreeMap::new();
+        let mut weekly: BTreeMap<String, Period> = BTreeMap::new();
+        for t in tests {
+            let date = day(&t.completed);
+            for period in [
+                daily.entry(date).or_default(),
+                weekly.entry(week(date)).or_default(),
+            ] {
+                period.samples += 1;
+                period.samples_passed += t.succeeded as usize;
+                period.practice_seconds += t.duration().num_seconds();
+                period.chars_typed += t.length();
+            }
+        }
+        for t in timed_tests {
+            let date = day(&t.completed);
+            for period in [
+                daily.entry(date).or_default(),
+                weekly.entry(week(date)).or_default(),
+            ] {
+                period.practice_seconds += t.duration().num_seconds();
+                period.chars_typed += t.end_index - t.start_index;
+            }
+        }
+        for k in keypresses {
+            let date = day(&k.time);
+            for period in [
+                daily.entry(date).or_default(),
+                weekly.entry(week(date)).or_default(),
+            ] {
+                period.keypresses += 1;
+                period.correct_keypresses += k.correct as usize;
+            }
+        }
+        let finish = |(period, mut p): (String, Period)| {
+            p.period = period;
+            if p.practice_seconds > 0 {
+                p.wpm = Some(p.chars_typed as f64 / 5.0 / (p.practice_seconds as f64 / 60.0));
+            }
+            if p.keypresses > 0 {
+                p.accuracy = Some(p.correct_keypresses as f64 / p.keypresses as f64);
+            }
+            p
+        };
+
+        Report {
+            book_title: book_title.to_owned(),
+            book_len: book_text.len(),
+            position,
+            progress_percent: position as f64 / usize::max(book_text.len(), 1) as f64 * 100.0,
+            practice_seconds: tests
+                .iter()
+                .map(|t| t.duration().num_seconds())
+                .sum::<i64>()
+                + timed_tests
+                    .iter()
+                    .map(|t| t.duration().num_seconds())
+                    .sum::<i64>(),
+            samples: tests.len(),
+            samples_passed,
+            success_rate: (!tests.is_empty()).then(|| samples_passed as f64 / tests.len() as f64),
+            timed_tests: timed_tests.len(),
+            longest_success,
+            daily: daily
+                .into_iter()
+                .map(|(date, p)| (date.to_string(), p))
+                .map(finish)
+                .collect(),
+            weekly: weekly.into_iter().map(finish).collect(),
+            most_missed,
+            slowest_bigrams: analysis::slowest_ngrams(keypresses, 2, SLOWEST_NGRAMS),
+            slowest_trigrams: analysis::slowest_ngrams(keypresses, 3, SLOWEST_NGRAMS),
+            sessions,
+            goals: None,
+        }
+    }
+}
+
+/// Formats a number of seconds as hours, minutes and seconds.
+pub fn format_seconds(seconds: i64) -> String {
+    format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+fn format_optional(value: Option<f64>, scale: f64, suffix: &str) -> String {
+    value.map_or("-".to_owned(), |v| format!("{:.1}{}", v * scale, suffix))
+}
+
+fn write_ngrams(f: &mut fmt::Formatter, ngrams: &[NgramStats]) -> fmt::Result {
+    writeln!(
+        f,
+        "  {:<8}{:>8}{:>8}{:>8}{:>10}",
+        "", "mean", "median", "errors", "attempts"
+    )?;
+    for n in ngrams {
+        writeln!(
+            f,
+            "  {:<8}{:>8}{:>8}{:>8}{:>10}",
+            format!("{:?}", n.ngram),
+            format_optional(n.mean_latency_ms, 1.0, "ms"),
+            format_optional(n.median_latency_ms, 1.0, "ms"),
+            format_optional(Some(n.error_rate), 100.0, "%"),
+            n.attempts,
+        )?;
+    }
+    Ok(())
+}
+
+fn write_sessions(f: &mut fmt::Formatter, sessions: &[Session]) -> fmt::Result {
+    writeln!(
+        f,
+        "  {:<18}{:>9}{:>8}{:>10}{:>10}{:>8}{:>10}",
+        "", "samples", "passed", "advanced", "time", "WPM", "accuracy"
+    )?;
+    for s in sessions.iter().skip(sessions.len().saturating_sub(RECENT_SESSIONS)) {
+        writeln!(
+            f,
+            "  {:<18}{:>9}{:>8}{:>10}{:>10}{:>8}{:>10}",
+            s.started.with_timezone(&Local).format("%Y-%m-%d %H:%M"),
+            s.samples,
+            s.samples_passed,
+            s.chars_advanced,
+            format_seconds(s.practice_seconds),
+            format_optional(s.average_wpm, 1.0, ""),
+            format_optional(s.accuracy, 100.0, "%"),
+        )?;
+    }
+    Ok(())
+}
+
+fn write_periods(f: &mut fmt::Formatter, periods: &[Period], recent: usize) -> fmt::Result {
+    writeln!(
+        f,
+        "  {:<12}{:>9}{:>8}{:>10}{:>8}{:>10}",
+        "", "samples", "passed", "time", "WPM", "accuracy"
+    )?;
+    for p in periods.iter().skip(periods.len().saturating_sub(recent)) {
+        writeln!(
+            f,
+            "  {:<12}{:>9}{:>8}{:>10}{:>8}{:>10}",
+            p.period,
+            p.samples,
+            p.samples_passed,
+            format_seconds(p.practice_seconds),
+            format_optional(p.wpm, 1.0, ""),
+            format_optional(p.accuracy, 100.0, "%"),
+        )?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", self.book_title)?;
+        writeln!(
+            f,
+            "Progress: {}/{} characters ({:.1}%)",
+            self.position, self.book_len, self.progress_percent
+        )?;
+        writeln!(
+            f,
+            "Practice time: {}",
+            format_seconds(self.practice_seconds)
+        )?;
+        writeln!(
+            f,
+            "Samples: {} attempted, {} passed, success rate {}",
+            self.samples,
+            self.samples_passed,
+            format_optional(self.success_rate, 100.0, "%")
+        )?;
+        writeln!(f, "Timed tests: {}", self.timed_tests)?;
+        if let Some(goals) = &self.goals {
+            if let (Some(goal), Some(today)) = (goals.daily_goal, goals.today) {
+                writeln!(
+                    f,
+                    "Daily goal: {:.0}/{} today, met {} in a row",
+                    today,
+                    goal,
+                    goals::format_days(goals.goal_streak.unwrap_or_default())
+                )?;
+            }
+            writeln!(
+                f,
+                "Practice streak: {}, longest {}",
+                goals::format_days(goals.current_streak),
+                goals::format_days(goals.longest_streak)
+            )?;
+        }
+        if let Some(s) = &self.longest_success {
+            writeln!(
+                f,
+                "Longest successful sample: {} characters at {} on {}",
+                s.length,
+                s.start_index,
+                s.completed.with_timezone(&Local).format("%Y-%m-%d")
+            )?;
+        }
+
+        writeln!(f, "\nSessions (last {}):", RECENT_SESSIONS)?;
+        write_sessions(f, &self.sessions)?;
+
+        writeln!(f, "\nDaily (last {} days practiced):", RECENT_DAYS)?;
+        write_periods(f, &self.daily, RECENT_DAYS)?;
+        writeln!(f, "\nWeekly (last {} weeks practiced):", RECENT_WEEKS)?;
+        write_periods(f, &self.weekly, RECENT_WEEKS)?;
+
+        writeln!(f, "\nMost missed characters:")?;
+        for m in &self.most_missed {
+            writeln!(f, "  {:?} {}", m.character, m.count)?;
+        }
+
+        writeln!(f, "\nSlowest bigrams:")?;
+        write_ngrams(f, &self.slowest_bigrams)?;
+        writeln!(f, "\nSlowest trigrams:")?;
+        write_ngrams(f, &self.slowest_trigrams)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,250 @@
+use crate::app::AppResult;
+use crate::config::{Config, StyleConfig, ThemeConfig};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use std::env;
+use std::str::FromStr;
+
+/// Name of the theme used when the config doesn't choose one.
+pub const DEFAULT_THEME: &str = "dark";
+/// Names of the built-in themes.
+pub const BUILT_IN_THEMES: [&str; 3] = ["dark", "light", "high_contrast"];
+
+/// Styles of the text, the frame around it and the panels drawn over it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    /// Text of the sample which has been typed.
+    pub typed: Style,
+    /// Character under the cursor.
+    pub current: Style,
+    /// Text of the sample which is still to be typed.
+    pub pending: Style,
+    /// Text around the sample.
+    pub context: Style,
+    pub border: Style,
+    /// Character the ghost is on.
+    pub ghost: Style,
+    /// Matches of a search, of which the selected one is also bold and
+    /// underlined.
+    pub search: Style,
+    /// Character where a sample failed, and the character typed instead.
+    pub mistake: Style,
+    /// Progress through the book, filled in with the foreground.
+    pub gauge: Style,
+    /// Samples which were passed and failed, in the charts and when
+    /// inspecting a word.
+    pub passed: Style,
+    pub failed: Style,
+    /// Charts of the sample length, WPM and accuracy.
+    pub chart_length: Style,
+    pub chart_wpm: Style,
+    pub chart_accuracy: Style,
+    /// Keys of the heatmap at the bottom, middle and top of the scale. Keys
+    /// in between blend the backgrounds when they are RGB colors, and take
+    /// the nearest style otherwise.
+    pub heat_low: Style,
+    pub heat_mid: Style,
+    pub heat_high: Style,
+    /// Keys of the heatmap with nothing recorded.
+    pub heat_none: Style,
+    /// Whether colors are left out, as requested by `NO_COLOR`.
+    pub no_color: bool,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::built_in(DEFAULT_THEME).unwrap()
+    }
+}
+
+impl Theme {
+    /// Looks up a built-in theme by name.
+    pub fn built_in(name: &str) -> Option<Self> {
+        let green = Color::Rgb(0, 255, 0);
+        let yellow = Color::Rgb(255, 255, 0);
+        let red = Color::Rgb(255, 0, 0);
+        match name {
+            "dark" => Some(Theme {
+                typed: Style::new().white(),
+                current: Style::new().black().on_white(),
+                pending: Style::new().blue(),
+                context: Style::new().dim(),
+                border: Style::new().white(),
+                ghost: Style::new().black().on_magenta(),
+                search: Style::new().black().on_yellow(),
+                mistake: Style::new().white().on_red().bold(),
+                gauge: Style::new().blue().on_black(),
+                passed: Style::new().green(),
+                failed: Style::new().red(),
+                chart_length: Style::new().blue(),
+                chart_wpm: Style::new().yellow(),
+                chart_accuracy: Style::new().cyan(),
+                heat_low: Style::new().black().bg(green),
+                heat_mid: Style::new().black().bg(yellow),
+                heat_high: Style::new().black().bg(red),
+                heat_none: Style::new().white().on_dark_gray(),
+                no_color: false,
+            }),
+            "light" => Some(Theme {
+                typed: Style::new().black(),
+                current: Style::new().white().on_black(),
+                pending: Style::new().blue(),
+                context: Style::new().gray(),
+                border: Style::new().black(),
+                ghost: Style::new().white().on_magenta(),
+                search: Style::new().black().on_light_yellow(),
+                mistake: Style::new().white().on_red(),
+                gauge: Style::new().white().on_blue(),
+                passed: Style::new().green(),
+                failed: Style::new().red(),
+                chart_length: Style::new().blue(),
+                chart_wpm: Style::new().magenta(),
+                chart_accuracy: Style::new().green(),
+                heat_low: Style::new().black().bg(Color::Rgb(120, 220, 120)),
+                heat_mid: Style::new().black().bg(Color::Rgb(240, 220, 100)),
+                heat_high: Style::new().black().bg(Color::Rgb(240, 120, 120)),
+                heat_none: Style::new().black().on_gray(),
+                no_color: false,
+            }),
+            "high_contrast" => Some(Theme {
+                typed: Style::new().white().on_black().bold(),
+                current: Style::new().black().on_yellow().bold(),
+                pending: Style::new().light_cyan().on_black().underlined(),
+                context: Style::new().gray().on_black(),
+                border: Style::new().white().bold(),
+                ghost: Style::new().black().on_light_magenta().bold(),
+                search: Style::new().black().on_light_green().bold(),
+                mistake: Style::new().white().on_light_red().bold(),
+                gauge: Style::new().light_cyan().on_black(),
+                passed: Style::new().light_green(),
+                failed: Style::new().light_red(),
+                chart_length: Style::new().light_cyan(),
+                chart_wpm: Style::new().light_yellow(),
+                chart_accuracy: Style::new().light_magenta(),
+                heat_low: Style::new().black().bg(green).bold(),
+                heat_mid: Style::new().black().bg(yellow).bold(),
+                heat_high: Style::new().black().bg(red).bold(),
+                heat_none: Style::new().white().on_black(),
+                no_color: false,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Styles which only use text attributes, for terminals without color.
+    pub fn monochrome() -> Self {
+        Theme {
+            typed: Style::new(),
+            current: Style::new().reversed(),
+            pending: Style::new().bold(),
+            context: Style::new().dim(),
+            border: Style::new(),
+            ghost: Style::new().bold().underlined(),
+            search: Style::new().italic().reversed(),
+            mistake: Style::new().bold().crossed_out().reversed(),
+            gauge: Style::new().reversed(),
+            passed: Style::new(),
+            failed: Style::new().dim(),
+            chart_length: Style::new(),
+            chart_wpm: Style::new(),
+            chart_accuracy: Style::new(),
+            heat_low: Style::new().dim(),
+            heat_mid: Style::new(),
+            heat_high: Style::new().reversed(),
+            heat_none: Style::new().dim(),
+            no_color: true,
+        }
+    }
+
+    /// Style of a key of the heatmap, from 0 at the bottom of the scale to
+    /// 1 at the top.
+    pub fn heat(&self, heat: f64) -> Style {
+        let heat = heat.clamp(0.0, 1.0) * 2.0;
+        let (from, to, heat) = match heat < 1.0 {
+            true => (self.heat_low, self.heat_mid, heat),
+            false => (self.heat_mid, self.heat_high, heat - 1.0),
+        };
+        match (from.bg, to.bg) {
+            (Some(Color::Rgb(r0, g0, b0)), Some(Color::Rgb(r1, g1, b1))) => {
+                let blend = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * heat) as u8;
+                from.bg(Color::Rgb(blend(r0, r1), blend(g0, g1), blend(b0, b1)))
+            }
+            _ if heat < 0.5 => from,
+            _ => to,
+        }
+    }
+
+    /// Resolves the theme chosen in the config, which is monochrome whenever
+    /// the `NO_COLOR` environment variable is set.
+    pub fn from_config(config: &Config) -> AppResult<Self> {
+        let name = config.theme.as_deref().unwrap_or(DEFAULT_THEME);
+        let theme = match config.themes.get(name) {
+            Some(custom) => custom_theme(name, custom)?,
+            None => Theme::built_in(name).ok_or_else(|| {
+                format!(
+                    "Unknown theme {}, expected one of {} or a theme in \"themes\"",
+                    name,
+                    BUILT_IN_THEMES.join(", ")
+                )
+            })?,
+        };
+        if env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+            return Ok(Theme::monochrome());
+        }
+        Ok(theme)
+    }
+}
+
+/// Builds a theme from the config, starting from its base theme.
+fn custom_theme(name: &str, custom: &ThemeConfig) -> AppResult<Theme> {
+    let base = custom.base.as_deref().unwrap_or(DEFAULT_THEME);
+    let mut theme = Theme::built_in(base)
+        .ok_or_else(|| format!("Unknown base theme {} of theme {}", base, name))?;
+    for (style, config) in [
+        (&mut theme.typed, &custom.typed),
+        (&mut theme.current, &custom.current),
+        (&mut theme.pending, &custom.pending),
+        (&mut theme.context, &custom.context),
+        (&mut theme.border, &custom.border),
+        (&mut theme.ghost, &custom.ghost),
+        (&mut theme.search, &custom.search),
+        (&mut theme.mistake, &custom.mistake),
+        (&mut theme.gauge, &custom.gauge),
+        (&mut theme.passed, &custom.passed),
+        (&mut theme.failed, &custom.failed),
+        (&mut theme.chart_length, &custom.chart_length),
+        (&mut theme.chart_wpm, &custom.chart_wpm),
+        (&mut theme.chart_accuracy, &custom.chart_accuracy),
+        (&mut theme.heat_low, &custom.heat_low),
+        (&mut theme.heat_mid, &custom.heat_mid),
+        (&mut theme.heat_high, &custom.heat_high),
+        (&mut theme.heat_none, &custom.heat_none),
+    ] {
+        if let Some(config) = config {
+            *style = parse_style(config).map_err(|e| format!("Invalid theme {}: {}", name, e))?;
+        }
+    }
+    Ok(theme)
+}
+
+fn parse_style(config: &StyleConfig) -> Result<Style, String> {
+    let color = |name: &str| Color::from_str(name).map_err(|_| format!("unknown color {}", name));
+    let mut style = Style::new();
+    if let Some(fg) = &config.fg {
+        style = style.fg(color(fg)?);
+    }
+    if let Some(bg) = &config.bg {
+        style = style.bg(color(bg)?);
+    }
+    for name in &config.modifiers {
+        style = style.add_modifier(match name.as_str() {
+            "bold" => Modifier::BOLD,
+            "dim" => Modifier::DIM,
+            "italic" => Modifier::ITALIC,
+            "underlined" => Modifier::UNDERLINED,
+            "reversed" => Modifier::REVERSED,
+            "crossed_out" => Modifier::CROSSED_OUT,
+            _ => return Err(format!("unknown modifier {}", name)),
+        });
+    }
+    Ok(style)
+}
@@ -28,10 +28,14 @@ impl<B: Backend> Tui<B> {
 
     /// Initializes the terminal interface.
     ///
-    /// It enables the raw mode and sets terminal properties.
-    pub fn init(&mut self) -> AppResult<()> {
+    /// It enables the raw mode and sets terminal properties, capturing the
+    /// mouse if `mouse_capture` is set.
+    pub fn init(&mut self, mouse_capture: bool) -> AppResult<()> {
         terminal::enable_raw_mode()?;
-        crossterm::execute!(io::stderr(), EnterAlternateScreen, EnableMouseCapture)?;
+        crossterm::execute!(io::stderr(), EnterAlternateScreen)?;
+        if mouse_capture {
+            crossterm::execute!(io::stderr(), EnableMouseCapture)?;
+        }
 
         // Define a custom panic hook to reset the terminal properties.
         // This way, you won't have your terminal messed up if an unexpected error happens.
@@ -0,0 +1,88 @@
+use ratatui::{buffer::Buffer, layout::Rect, style::Style, widgets::Widget};
+use std::ops::Range;
+
+/// Text wrapped into lines, with styles given to ranges of its characters,
+/// such as the part typed so far or the character under the cursor.
+///
+/// Characters are addressed by their index in the unwrapped text, and
+/// `line_index[i]` is the line and column character `i` is drawn at. Ranges
+/// are applied in the order they are added, each one patching the styles of
+/// those before it, and characters outside all of them have the base style.
+#[derive(Debug, Clone)]
+pub struct TypingText<'a> {
+    lines: &'a [String],
+    line_index: &'a [(usize, usize)],
+    first_line: usize,
+    style: Style,
+    ranges: Vec<(Range<usize>, Style)>,
+}
+
+impl<'a> TypingText<'a> {
+    pub fn new(lines: &'a [String], line_index: &'a [(usize, usize)]) -> Self {
+        TypingText {
+            lines,
+            line_index,
+            first_line: 0,
+            style: Style::default(),
+            ranges: Vec::new(),
+        }
+    }
+
+    /// Line drawn at the top of the area.
+    pub fn first_line(mut self, first_line: usize) -> Self {
+        self.first_line = first_line;
+        self
+    }
+
+    /// Style of the characters outside all of the ranges.
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Styles a range of character indices, on top of earlier ranges.
+    pub fn range(mut self, range: Range<usize>, style: Style) -> Self {
+        self.ranges.push((range, style));
+        self
+    }
+
+    /// Indices of the characters on the lines which fit in a given height.
+    pub fn visible(&self, height: u16) -> Range<usize> {
+        let last_line = self.first_line + height as usize;
+        let start = self
+            .line_index
+            .partition_point(|&(line, _)| line < self.first_line);
+        let end = self
+            .line_index
+            .partition_point(|&(line, _)| line < last_line);
+        start..end
+    }
+}
+
+impl Widget for TypingText<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let lines = self.lines.iter().skip(self.first_line);
+        for (y, line) in (area.top()..area.bottom()).zip(lines) {
+            buf.set_stringn(area.x, y, line, area.width as usize, Style::default());
+        }
+        let visible = self.visible(area.height);
+        let mut styles: Vec<Option<Style>> = vec![None; visible.len()];
+        for (range, style) in &self.ranges {
+            let start = usize::max(range.start, visible.start);
+            let end = usize::min(range.end, visible.end);
+            for i in start..end {
+                let patched = styles[i - visible.start].get_or_insert_with(Style::default);
+                *patched = patched.patch(*style);
+            }
+        }
+        for (&(line, column), style) in self.line_index[visible].iter().zip(styles) {
+            if column < area.width as usize {
+                buf.get_mut(
+                    area.x + column as u16,
+                    area.y + (line - self.first_line) as u16,
+                )
+                .set_style(style.unwrap_or(self.style));
+            }
+        }
+    }
+}
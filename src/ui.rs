@@ -6,132 +6,38 @@ use ratatui::{
 };
 use ratatui::{prelude::*, widgets::*};
 
-use crate::app::App;
+use crate::analysis::{KeyStats, NgramStats};
+use crate::app::{App, HeatmapView, Mode, Overlay, CHARTS_HEIGHT, CHARTS_WIDTH};
+use crate::goals;
+use crate::inspect::WordResult;
+use crate::keymap::{Action, Context};
+use crate::profile;
+use crate::progress;
+use crate::replay;
+use crate::stats::format_seconds;
+use crate::typing_text::TypingText;
 //TODO fix panic on end of short input
 /// Renders the user interface widgets. 
 pub fn render(app: &mut App, frame: &mut Frame) {
-    let &(start_line, start_offset) = app.line_index.get(app.sample_start_index).unwrap();
-    let &(cur_line, cur_offset) = app
+    let &(cur_line, _) = app
         .line_index
         .get(app.sample_start_index + app.cur_char)
         .unwrap();
-    let &(end_line, end_offset) = app
-        .line_index
-        .get(app.sample_start_index + app.sample_len)
-        .unwrap();
-    let mut lines: Vec<String> = app.book_lines.clone();
-    let num_rows = frame.size().height as usize - 2;
-    let rows_to_center = num_rows / 2 - 2;
+    let screen = Rect::new(0, 0, frame.size().width, frame.size().height);
+    let (text_area, charts_area, progress_area) = split_screen(app, screen);
+
+    let num_rows = (text_area.height as usize).saturating_sub(2);
+    let rows_to_center = (num_rows / 2).saturating_sub(2);
 
     if app.following_typing {
         app.display_line = cur_line
     }
 
-    app.display_line = usize::min(app.display_line, lines.len());
+    app.display_line = usize::min(app.display_line, app.book_lines.len());
 
     let first_row = usize::checked_sub(rows_to_center, app.display_line).unwrap_or(0);
 
     let num_skipped_lines = usize::checked_sub(app.display_line, rows_to_center).unwrap_or(0);
-    lines = lines.split_off(usize::min(num_skipped_lines, lines.len()));
-    lines.truncate(num_rows - first_row);
-
-    let mut display_lines: Vec<Line> = Vec::new();
-    for (mut i, s) in lines.iter().enumerate() {
-        i += num_skipped_lines;
-        if i == cur_line {
-            if i == start_line && i == end_line {
-                display_lines.push(Line::from(vec![
-                    s.chars().take(start_offset).collect::<String>().dim(),
-                    s.chars()
-                        .take(cur_offset)
-                        .skip(start_offset)
-                        .collect::<String>()
-                        .white(),
-                    s.chars()
-                        .nth(cur_offset)
-                        .unwrap()
-                        .to_string()
-                        .black()
-                        .bg(Color::White),
-                    s.chars()
-                        .take(end_offset)
-                        .skip(cur_offset + 1)
-                        .collect::<String>()
-                        .blue(),
-                    s.chars().skip(end_offset).collect::<String>().dim(),
-                ]));
-            } else if i == start_line {
-                display_lines.push(Line::from(vec![
-                    s.chars().take(start_offset).collect::<String>().dim(),
-                    s.chars()
-                        .take(cur_offset)
-                        .skip(start_offset)
-                        .collect::<String>()
-                        .white(),
-                    s.chars()
-                        .nth(cur_offset)
-                        .unwrap()
-                        .to_string()
-                        .black()
-                        .bg(Color::White),
-                    s.chars().skip(cur_offset + 1).collect::<String>().blue(),
-                ]));
-            } else if i == end_line {
-                display_lines.push(Line::from(vec![
-                    s.chars().take(cur_offset).collect::<String>().white(),
-                    s.chars()
-                        .nth(cur_offset)
-                        .unwrap()
-                        .to_string()
-                        .black()
-                        .bg(Color::White),
-                    s.chars()
-                        .take(end_offset)
-                        .skip(cur_offset + 1)
-                        .collect::<String>()
-                        .blue(),
-                    s.chars().skip(end_offset).collect::<String>().dim(),
-                ]));
-            } else {
-                display_lines.push(Line::from(vec![
-                    s.chars().take(cur_offset).collect::<String>().white(),
-                    s.chars()
-                        .nth(cur_offset)
-                        .unwrap()
-                        .to_string()
-                        .black()
-                        .bg(Color::White),
-                    s.chars().skip(cur_offset + 1).collect::<String>().blue(),
-                ]));
-            }
-        } else if i < cur_line {
-            if i == start_line {
-                display_lines.push(Line::from(vec![
-                    s.chars().take(start_offset).collect::<String>().dim(),
-                    s.chars().skip(start_offset).collect::<String>().white(),
-                ]));
-            } else if i < start_line {
-                display_lines.push(s.clone().dim().into());
-            } else {
-                display_lines.push(s.clone().white().into());
-            }
-        } else {
-            if i == end_line {
-                display_lines.push(Line::from(vec![
-                    s.chars().take(end_offset).collect::<String>().blue(),
-                    s.chars().skip(end_offset).collect::<String>().dim(),
-                ]));
-            } else if i < end_line {
-                display_lines.push(s.clone().blue().into());
-            } else {
-                display_lines.push(s.clone().dim().into());
-            }
-        }
-    }
-
-    let graph = Paragraph::new::<Text>(display_lines.into()).style(Style::default());
-
-    let screen = Rect::new(0, 0, frame.size().width, frame.size().height);
 
     let vert = Layout::default()
         .direction(Direction::Vertical)
@@ -139,7 +45,7 @@ pub fn render(app: &mut App, frame: &mut Frame) {
             Constraint::Length(first_row as u16 + 1),
             Constraint::Percentage(100),
         ])
-        .split(screen);
+        .split(text_area);
     let horiz = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([
@@ -148,18 +54,889 @@ pub fn render(app: &mut App, frame: &mut Frame) {
             Constraint::Percentage((100 - app.text_width_percent) / 2),
         ])
         .split(vert[1])[1];
+    let horiz = Rect {
+        height: u16::min(horiz.height, (num_rows - first_row) as u16),
+        ..horiz
+    };
 
     // Render into the second chunk of the layout.
-    frame.render_widget(graph, horiz);
+    frame.render_widget(book_view(app, num_skipped_lines, horiz.height), horiz);
+    render_mistake(app, frame, horiz, num_skipped_lines);
+    app.text_area = horiz;
+    app.first_drawn_line = num_skipped_lines;
+    let status = if let Some(name) = &app.bookmark_name {
+        format!("Bookmark name: {}_", name)
+    } else if let Some(search) = &app.search {
+        let matches = match search.matches.len() {
+            _ if search.query.is_empty() => String::new(),
+            0 => " | No matches".to_owned(),
+            n => format!(" | {}/{}", search.current + 1, n),
+        };
+        format!(
+            "Search: {}{}{}",
+            search.query,
+            if search.editing { "_" } else { "" },
+            matches
+        )
+    } else if let Some(replay) = &app.replay {
+        format!(
+            "Replay {}/{} | {}x{} | {}",
+            format_seconds(replay.position.num_seconds()),
+            format_seconds(replay.length().num_seconds()),
+            replay.speed,
+            if replay.paused { " paused" } else { "" },
+            replay::format_time(replay.frame().time)
+        )
+    } else {
+        match (&app.timed_result, app.time_remaining()) {
+            (Some(result), _) => format!(
+                "Time! Net {:.0} WPM | Gross {:.0} WPM | {:.1}% accuracy",
+                result.net_wpm,
+                result.gross_wpm,
+                result.accuracy * 100.0
+            ),
+            (None, Some(remaining)) => {
+                let seconds = (remaining.num_milliseconds() + 999) / 1000;
+                format!("{}:{:02}", seconds / 60, seconds % 60)
+            }
+            (None, None) if app.mode == Mode::Drill => {
+                let targets = app
+                    .drill_targets
+                    .keys
+                    .iter()
+                    .map(char::to_string)
+                    .chain(app.drill_targets.bigrams.iter().cloned())
+                    .map(|target| target.replace(' ', "\u{2423}"))
+                    .collect::<Vec<_>>();
+                format!(
+                    "Drill {}/{} | Targets: {}",
+                    app.drill_index + 1,
+                    app.drill_passages.len(),
+                    targets.join(" ")
+                )
+            }
+            (None, None) => {
+                let mut average = format!("Avg length: {}", app.get_rolling_average());
+                if let Some(outcome) = app.ghost_outcome {
+                    average = format!("{} | {}", outcome, average);
+                }
+                match app.get_rolling_speed() {
+                    Some((wpm, accuracy)) => format!(
+                        "{} | WPM: {:.0} | Accuracy: {:.1}%",
+                        average,
+                        wpm,
+                        accuracy * 100.0
+                    ),
+                    None => average,
+                }
+            }
+        }
+    };
+
+    let status = match app.paused_since {
+        Some(_) => format!("Paused, type to resume | {}", status),
+        None => status,
+    };
+
+    let mut title = vec!["BookTyping".to_owned()];
+    if app.replay.is_none() {
+        let (elapsed, wpm) = app.live_speed();
+        let seconds = elapsed.num_seconds();
+        title.push(match wpm {
+            Some(wpm) => format!("{}:{:02} {:.0} WPM", seconds / 60, seconds % 60, wpm),
+            None => format!("{}:{:02}", seconds / 60, seconds % 60),
+        });
+    }
+    title.push(app.clock.time_of_day());
+
+    let activity = app.activity();
+    let today = goals::local_date(app.clock.now());
+    if let Some(goal) = app.config.daily_goal {
+        let done = activity.day(today).toward(goal);
+        title.push(format!(
+            "Goal: {:.0}/{} {}{}",
+            f64::min(done, goal.target()).floor(),
+            goal.target(),
+            goal.unit(),
+            if done >= goal.target() { " \u{2713}" } else { "" }
+        ));
+    }
+    match activity.current_streak(today, goals::practiced) {
+        0 => {}
+        streak => title.push(format!("Streak: {}", goals::format_days(streak))),
+    }
+    if let Some(key) = app.keymap.keys(Action::Help).next() {
+        title.push(format!("{} help", key));
+    }
+    // Leave out the last parts rather than run into the status.
+    let mut title = title.join(" | ");
+    while title.chars().count() + status.chars().count() + 4 > screen.width as usize {
+        match title.rfind(" | ") {
+            Some(i) => title.truncate(i),
+            None => break,
+        }
+    }
+
     frame.render_widget(
         Block::default()
-            .title("BookTyping")
-            .title(
-                block::Title::from(format!("{}", app.get_rolling_average().unwrap()))
-                    .alignment(Alignment::Right),
-            )
+            .title(title)
+            .title(block::Title::from(status).alignment(Alignment::Right))
             .borders(Borders::ALL)
-            .border_style(Style::new().white()),
+            .border_style(app.theme.border),
         screen,
     );
+
+    if let Some(area) = charts_area {
+        render_charts(app, frame, area);
+    }
+    render_progress(app, frame, progress_area);
+
+    match app.overlay {
+        Some(Overlay::Heatmap(view)) => render_heatmap(app, view, frame),
+        Some(Overlay::Transitions) => render_transitions(app, frame),
+        Some(Overlay::Help) => render_help(app, frame),
+        Some(Overlay::Inspect) => render_inspection(app, frame),
+        Some(Overlay::Bookmarks) => render_bookmarks(app, frame),
+        None => {}
+    }
+
+    if app.profile_picker.is_some() {
+        render_profile_picker(app, frame);
+    }
+
+    if app.summary.is_some() {
+        render_summary(app, frame);
+    }
+
+    if app.theme.no_color {
+        for cell in &mut frame.buffer_mut().content {
+            cell.fg = Color::Reset;
+            cell.bg = Color::Reset;
+        }
+    }
+}
+
+/// Text of the book as it is drawn while practicing, from `first_line` and
+/// with the sample, the cursor, the matches of a search and the ghost styled.
+pub fn book_view(app: &App, first_line: usize, height: u16) -> TypingText<'_> {
+    let theme = app.theme;
+    let cursor = app.sample_start_index + app.cur_char;
+    let sample_end = app.sample_start_index + app.sample_len;
+    let mut text = TypingText::new(&app.book_lines, &app.line_index)
+        .first_line(first_line)
+        .range(0..app.sample_start_index, theme.context)
+        .range(app.sample_start_index..cursor, theme.typed)
+        .range(cursor..cursor + 1, theme.current)
+        .range(cursor + 1..sample_end, theme.pending)
+        .range(sample_end..app.line_index.len(), theme.context);
+    if let Some(search) = &app.search {
+        let visible = text.visible(height);
+        let first = search.matches.partition_point(|&i| i < visible.start);
+        for (n, &start) in search.matches.iter().enumerate().skip(first) {
+            if start >= visible.end {
+                break;
+            }
+            let mut style = theme.search;
+            if n == search.current {
+                style = style.add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+            }
+            text = text.range(start..start + search.query.len(), style);
+        }
+    }
+    // The ghost isn't marked when the cursor is on it too.
+    if let Some(ghost) = app.ghost_index().filter(|&ghost| ghost != cursor) {
+        text = text.range(ghost..ghost + 1, theme.ghost);
+    }
+    if let Some((index, _)) = app.shown_mistake() {
+        text = text.range(index..index + 1, theme.mistake);
+    }
+    text
+}
+
+/// Shows the character typed where the last sample failed, above the place
+/// it failed or below it on the top line.
+fn render_mistake(app: &App, frame: &mut Frame, area: Rect, first_line: usize) {
+    let Some((index, mistyped)) = app.shown_mistake() else {
+        return;
+    };
+    let Some(&(line, column)) = app.line_index.get(index) else {
+        return;
+    };
+    let row = match line.checked_sub(first_line) {
+        Some(0) => 1,
+        Some(row) => row - 1,
+        None => return,
+    };
+    if row < area.height as usize && column < area.width as usize {
+        let symbol = if mistyped == ' ' { '\u{2423}' } else { mistyped };
+        let cell = frame
+            .buffer_mut()
+            .get_mut(area.x + column as u16, area.y + row as u16);
+        cell.reset();
+        cell.set_char(symbol).set_style(app.theme.mistake);
+    }
+}
+
+/// Rows of a US keyboard, with the indent of each row in columns.
+const KEYBOARD_ROWS: [(&str, usize); 4] = [
+    ("`1234567890-=", 0),
+    ("qwertyuiop[]\\", 2),
+    ("asdfghjkl;'", 3),
+    ("zxcvbnm,./", 5),
+];
+const KEY_WIDTH: usize = 4;
+
+/// Returns a rectangle of the given size centered in `area`.
+fn centered_rect(width: u16, height: u16, area: Rect) -> Rect {
+    let width = u16::min(width, area.width);
+    let height = u16::min(height, area.height);
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
+}
+
+fn render_heatmap(app: &App, view: HeatmapView, frame: &mut Frame) {
+    let value = |stats: &KeyStats| match view {
+        HeatmapView::Missed => stats.miss_rate(),
+        HeatmapView::Mistyped => stats.mistype_rate(),
+        HeatmapView::Latency => stats.mean_latency_ms(),
+    };
+    let format_value = |value: f64| match view {
+        HeatmapView::Latency => format!("{:.0}ms", value),
+        _ => format!("{:.1}%", value * 100.0),
+    };
+    let values = app.key_stats.values().filter_map(value).collect::<Vec<_>>();
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+
+    let key_span = |key: char, label: String| {
+        let style = match app.key_stats.get(&key).and_then(value) {
+            Some(v) if max > min => app.theme.heat((v - min) / (max - min)),
+            Some(_) => app.theme.heat(0.0),
+            None => app.theme.heat_none,
+        };
+        Span::styled(label, style)
+    };
+
+    let mut lines: Vec<Line> = Vec::new();
+    for (keys, indent) in KEYBOARD_ROWS {
+        let mut spans = vec![Span::raw(" ".repeat(indent))];
+        for key in keys.chars() {
+            spans.push(key_span(key, format!(" {} ", key)));
+            spans.push(Span::raw(" ".repeat(KEY_WIDTH - 3)));
+        }
+        lines.push(Line::from(spans));
+        lines.push(Line::default());
+    }
+    lines.push(Line::from(vec![
+        Span::raw(" ".repeat(4 * KEY_WIDTH)),
+        key_span(' ', " ".repeat(6 * KEY_WIDTH)),
+    ]));
+    lines.push(Line::default());
+    lines.push(if values.is_empty() {
+        Line::from("No keypresses recorded yet")
+    } else {
+        Line::from(vec![
+            Span::raw(format!("{} ", format_value(min))),
+            Span::styled("   ", app.theme.heat_low),
+            Span::styled("   ", app.theme.heat_mid),
+            Span::styled("   ", app.theme.heat_high),
+            Span::raw(format!(" {}", format_value(max))),
+        ])
+    });
+
+    let title = match view {
+        HeatmapView::Missed => "Missed when expected",
+        HeatmapView::Mistyped => "Typed by mistake",
+        HeatmapView::Latency => "Mean latency",
+    };
+    let area = centered_rect(
+        (13 * KEY_WIDTH + 4) as u16,
+        lines.len() as u16 + 2,
+        frame.size(),
+    );
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(lines).block(
+            Block::default()
+                .title(format!("Key heatmap: {}", title))
+                .title(block::Title::from("Tab: next view").alignment(Alignment::Right))
+                .borders(Borders::ALL)
+                .border_style(app.theme.border),
+        ),
+        area,
+    );
+}
+
+fn ngram_table<'a>(title: &'a str, ngrams: &[NgramStats]) -> Table<'a> {
+    let format_ms = |ms: Option<f64>| ms.map_or("-".to_owned(), |ms| format!("{:.0}ms", ms));
+    Table::new(ngrams.iter().map(|n| {
+        Row::new(vec![
+            n.ngram.replace(' ', "\u{2423}"),
+            format_ms(n.mean_latency_ms),
+            format_ms(n.median_latency_ms),
+            format!("{:.1}%", n.error_rate * 100.0),
+            n.attempts.to_string(),
+        ])
+    }))
+    .header(Row::new(vec!["", "mean", "median", "errors", "count"]).bold())
+    .widths(&[
+        Constraint::Length(5),
+        Constraint::Length(7),
+        Constraint::Length(7),
+        Constraint::Length(7),
+        Constraint::Length(6),
+    ])
+    .block(Block::default().title(title))
+}
+
+fn render_transitions(app: &App, frame: &mut Frame) {
+    let area = centered_rect(
+        2 * 36 + 3,
+        u16::max(app.slowest_bigrams.len() as u16, 1) + 4,
+        frame.size(),
+    );
+    frame.render_widget(Clear, area);
+    let block = Block::default()
+        .title("Slowest transitions")
+        .borders(Borders::ALL)
+        .border_style(app.theme.border);
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    if app.slowest_bigrams.is_empty() {
+        frame.render_widget(Paragraph::new("Not enough keypresses recorded yet"), inner);
+        return;
+    }
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(inner);
+    frame.render_widget(ngram_table("Bigrams", &app.slowest_bigrams), columns[0]);
+    frame.render_widget(ngram_table("Trigrams", &app.slowest_trigrams), columns[1]);
+}
+
+/// Width of the profile picker, and of the columns of its table.
+const PROFILES_WIDTH: u16 = 80;
+const PROFILE_COLUMN_WIDTHS: [u16; 7] = [18, 8, 9, 5, 8, 9, 14];
+
+/// Draws the profiles with how each is doing on the book, the current one
+/// marked and the selected one highlighted, above the name of a new profile.
+fn render_profile_picker(app: &App, frame: &mut Frame) {
+    let Some(picker) = &app.profile_picker else {
+        return;
+    };
+    let rows = picker.standings.iter().enumerate().map(|(i, standing)| {
+        let mut columns = standing.columns();
+        let marker = if standing.profile == app.logs.profile { "* " } else { "  " };
+        columns[0] = format!("{}{}", marker, columns[0]);
+        let row = Row::new(columns);
+        match i == picker.selected && picker.name.is_empty() {
+            true => row.style(app.theme.current),
+            false => row,
+        }
+    });
+    // The profile names are indented to leave room for the marker.
+    let mut headings = profile::COLUMNS.map(str::to_owned);
+    headings[0] = format!("  {}", headings[0]);
+    let widths = PROFILE_COLUMN_WIDTHS.map(Constraint::Length);
+    let table = Table::new(rows)
+        .header(Row::new(headings).bold())
+        .widths(&widths);
+
+    let mut prompt = vec![Line::from(format!("New profile: {}_", picker.name))];
+    if let Some(error) = &picker.error {
+        prompt.push(Line::from(error.as_str()));
+    }
+    let table_height = picker.standings.len() as u16 + 1;
+    let area = centered_rect(
+        PROFILES_WIDTH,
+        table_height + prompt.len() as u16 + 3,
+        frame.size(),
+    );
+    frame.render_widget(Clear, area);
+    let block = Block::default()
+        .title("Profiles")
+        .title(block::Title::from("enter: switch | esc: close").alignment(Alignment::Right))
+        .borders(Borders::ALL)
+        .border_style(app.theme.border);
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(table_height),
+            Constraint::Length(1),
+            Constraint::Min(0),
+        ])
+        .split(inner);
+    frame.render_widget(table, rows[0]);
+    frame.render_widget(Paragraph::new(prompt).wrap(Wrap { trim: true }), rows[2]);
+}
+
+/// Splits the screen into the area for the text, the area for the charts
+/// panel if it is shown, and the row for the progress gauge, the last two
+/// inside the border.
+fn split_screen(app: &App, screen: Rect) -> (Rect, Option<Rect>, Rect) {
+    let progress_area = Rect::new(
+        1,
+        screen.height.saturating_sub(2),
+        screen.width.saturating_sub(2),
+        1,
+    );
+    let screen = Rect {
+        height: screen.height.saturating_sub(1),
+        ..screen
+    };
+    let (text_area, charts_area) = split_charts(app, screen);
+    (text_area, charts_area, progress_area)
+}
+
+/// Fewest rows, with its border, the text is left with by charts below it,
+/// and fewest rows worth drawing the charts in.
+const MIN_TEXT_HEIGHT: u16 = 8;
+const MIN_CHARTS_HEIGHT: u16 = 4;
+
+fn split_charts(app: &App, screen: Rect) -> (Rect, Option<Rect>) {
+    if !app.show_charts {
+        return (screen, None);
+    }
+    if app.charts_beside_text() {
+        let width = u16::min(CHARTS_WIDTH, screen.width);
+        (
+            Rect {
+                width: screen.width - width,
+                ..screen
+            },
+            Some(Rect::new(
+                screen.width - width,
+                1,
+                width - 1,
+                screen.height.saturating_sub(2),
+            )),
+        )
+    } else {
+        // Leave the text enough rows to type in, or leave the charts out.
+        let height = u16::min(
+            u16::min(CHARTS_HEIGHT, screen.height / 2),
+            screen.height.saturating_sub(MIN_TEXT_HEIGHT),
+        );
+        if height < MIN_CHARTS_HEIGHT {
+            return (screen, None);
+        }
+        (
+            Rect {
+                height: screen.height - height,
+                ..screen
+            },
+            Some(Rect::new(
+                1,
+                screen.height - height,
+                screen.width.saturating_sub(2),
+                height.saturating_sub(1),
+            )),
+        )
+    }
+}
+
+/// Lengths of the longest and the current run of passed samples.
+fn success_streaks(app: &App) -> (usize, usize) {
+    let mut best = 0;
+    let mut current = 0;
+    for t in &app.tests {
+        current = if t.succeeded { current + 1 } else { 0 };
+        best = usize::max(best, current);
+    }
+    (best, current)
+}
+
+fn line_chart<'a>(title: String, data: &'a [(f64, f64)], max: f64, style: Style) -> Chart<'a> {
+    let min = data.iter().map(|&(_, y)| y).fold(max, f64::min);
+    Chart::new(vec![Dataset::default()
+        .marker(symbols::Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(style)
+        .data(data)])
+    .block(Block::default().title(title))
+    .x_axis(Axis::default().bounds([0.0, f64::max(data.len() as f64 - 1.0, 1.0)]))
+    .y_axis(
+        Axis::default()
+            .bounds([min, max])
+            .labels(vec![
+                Span::raw(format!("{:.0}", min)),
+                Span::raw(format!("{:.0}", max)),
+            ]),
+    )
+}
+
+/// Renders charts of the recent samples into the charts panel.
+fn render_charts(app: &App, frame: &mut Frame, area: Rect) {
+    let chunks = Layout::default()
+        .direction(if app.charts_beside_text() {
+            Direction::Vertical
+        } else {
+            Direction::Horizontal
+        })
+        .constraints([Constraint::Ratio(1, 4); 4])
+        .split(area);
+    let recent = |width: u16| {
+        let count = usize::min(app.tests.len(), width as usize);
+        &app.tests[app.tests.len() - count..]
+    };
+
+    let tests = recent(chunks[0].width);
+    let lengths = tests.iter().map(|t| t.length() as u64).collect::<Vec<_>>();
+    frame.render_widget(
+        Sparkline::default()
+            .block(Block::default().title(format!("Sample length: {}", app.get_rolling_average())))
+            .data(&lengths)
+            .style(app.theme.chart_length),
+        chunks[0],
+    );
+
+    let tests = recent(chunks[1].width);
+    let (best, current) = success_streaks(app);
+    let outcomes = tests
+        .iter()
+        .map(|t| {
+            if t.succeeded {
+                "\u{2588}".set_style(app.theme.passed)
+            } else {
+                "\u{2588}".set_style(app.theme.failed)
+            }
+        })
+        .collect::<Vec<_>>();
+    frame.render_widget(
+        Paragraph::new(vec![
+            Line::from(outcomes),
+            Line::from(format!("Current streak: {}", current)),
+            Line::from(format!("Best streak: {}", best)),
+        ])
+        .block(Block::default().title("Passed and failed")),
+        chunks[1],
+    );
+
+    let tests = recent(chunks[2].width * 2);
+    let wpm = tests
+        .iter()
+        .filter_map(|t| t.wpm)
+        .enumerate()
+        .map(|(i, wpm)| (i as f64, wpm))
+        .collect::<Vec<_>>();
+    let max_wpm = wpm.iter().map(|&(_, wpm)| wpm).fold(0.0, f64::max).ceil();
+    let last_wpm = wpm.last().map_or(0.0, |&(_, wpm)| wpm);
+    frame.render_widget(
+        line_chart(format!("WPM: {:.0}", last_wpm), &wpm, max_wpm, app.theme.chart_wpm),
+        chunks[2],
+    );
+
+    let tests = recent(chunks[3].width * 2);
+    let accuracies = tests.iter().filter_map(|t| t.accuracy).collect::<Vec<_>>();
+    let rolling_accuracy = (0..accuracies.len())
+        .map(|i| {
+            let window = &accuracies[i.saturating_sub(9)..=i];
+            (i as f64, window.iter().sum::<f64>() / window.len() as f64 * 100.0)
+        })
+        .collect::<Vec<_>>();
+    let last_accuracy = rolling_accuracy.last().map_or(0.0, |&(_, a)| a);
+    frame.render_widget(
+        line_chart(
+            format!("Accuracy (last 10): {:.1}%", last_accuracy),
+            &rolling_accuracy,
+            100.0,
+            app.theme.chart_accuracy,
+        ),
+        chunks[3],
+    );
+}
+
+fn render_progress(app: &App, frame: &mut Frame, area: Rect) {
+    let progress = &app.progress;
+    let mut label = format!(
+        "{:.1}% | {} characters, {} words left",
+        progress.percent(),
+        progress.chars_remaining(),
+        progress.words_remaining
+    );
+    if let Some(time) = progress.time_remaining() {
+        label.push_str(&format!(" | ~{} of practice", progress::format_duration(time)));
+    }
+    if let Some(chapter) = &progress.chapter {
+        label = format!("{} | {}", chapter, label);
+    }
+    frame.render_widget(
+        Gauge::default()
+            .gauge_style(app.theme.gauge)
+            .ratio(f64::min(progress.percent() / 100.0, 1.0))
+            .label(label),
+        area,
+    );
+}
+
+fn render_summary(app: &App, frame: &mut Frame) {
+    let Some(session) = &app.summary else {
+        return;
+    };
+    let format_wpm = |wpm: Option<f64>| wpm.map_or("-".to_owned(), |wpm| format!("{:.0}", wpm));
+    let mut lines = vec![
+        Line::from(format!(
+            "Samples: {} attempted, {} passed",
+            session.samples, session.samples_passed
+        )),
+        Line::from(format!("Characters advanced: {}", session.chars_advanced)),
+        Line::from(format!(
+            "Time practiced: {}",
+            format_seconds(session.practice_seconds)
+        )),
+        Line::from(format!(
+            "WPM: {} average, {} best",
+            format_wpm(session.average_wpm),
+            format_wpm(session.best_wpm)
+        )),
+        Line::from(format!(
+            "Accuracy: {}",
+            session
+                .accuracy
+                .map_or("-".to_owned(), |a| format!("{:.1}%", a * 100.0))
+        )),
+    ];
+    if !session.most_missed.is_empty() {
+        lines.push(Line::from(format!(
+            "Most missed: {}",
+            session
+                .most_missed
+                .iter()
+                .map(|(c, count)| format!("{:?} ({})", c, count))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )));
+    }
+    lines.push(Line::default());
+    lines.push(Line::from("Press any key to exit".dim()));
+
+    let area = centered_rect(60, lines.len() as u16 + 2, frame.size());
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(lines).block(
+            Block::default()
+                .title("Session summary")
+                .borders(Borders::ALL)
+                .border_style(app.theme.border),
+        ),
+        area,
+    );
+}
+
+fn render_bookmarks(app: &App, frame: &mut Frame) {
+    let mut lines = Vec::new();
+    for (i, bookmark) in app.bookmarks.iter().enumerate() {
+        let line = format!(
+            "{:5.1}%  {:12} {}",
+            bookmark.index as f64 / usize::max(app.book_len(), 1) as f64 * 100.0,
+            app.chapter_at(bookmark.index).unwrap_or_default(),
+            bookmark.name
+        );
+        lines.push(if i == app.selected_bookmark {
+            line.set_style(app.theme.current).into()
+        } else {
+            Line::from(line)
+        });
+    }
+    if lines.is_empty() {
+        let key = app.keymap.keys(Action::AddBookmark).next();
+        lines.push(Line::from(match key {
+            Some(key) => format!("No bookmarks yet, press {} to add one", key),
+            None => "No bookmarks yet".to_owned(),
+        }));
+    }
+
+    let keys = |action| {
+        app.keymap
+            .keys(action)
+            .next()
+            .map_or(String::new(), |key| key.to_string())
+    };
+    let area = centered_rect(60, lines.len() as u16 + 2, frame.size());
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(lines).block(
+            Block::default()
+                .title("Bookmarks")
+                .title(
+                    block::Title::from(format!(
+                        "{}: go to | {}: delete",
+                        keys(Action::GoToBookmark),
+                        keys(Action::DeleteBookmark)
+                    ))
+                    .alignment(Alignment::Right),
+                )
+                .borders(Borders::ALL)
+                .border_style(app.theme.border),
+        ),
+        area,
+    );
+}
+
+/// Width of the help overlay, and of its column of keys.
+const HELP_WIDTH: u16 = 76;
+const HELP_KEYS_WIDTH: usize = 24;
+
+fn render_help(app: &App, frame: &mut Frame) {
+    let replaying = app.replay.is_some();
+    let heading = |text: &'static str| Line::from(text.bold());
+    let mut lines = vec![heading("Keys")];
+    for action in Action::ALL {
+        if action.context() == Context::Replay && !replaying {
+            continue;
+        }
+        let keys = app
+            .keymap
+            .keys(action)
+            .map(|key| key.to_string())
+            .collect::<Vec<_>>();
+        if !keys.is_empty() {
+            lines.push(Line::from(format!(
+                "  {:width$}{}",
+                keys.join(" / "),
+                action.description(),
+                width = HELP_KEYS_WIDTH
+            )));
+        }
+    }
+    if !replaying {
+        lines.push(Line::from(format!(
+            "  {:width$}{}",
+            "Any other key",
+            "Type the character under the cursor",
+            width = HELP_KEYS_WIDTH
+        )));
+    }
+
+    lines.push(Line::default());
+    lines.push(heading("Samples"));
+    let rules = match app.mode {
+        Mode::Samples | Mode::Replay(_) => [
+            "Each sample starts where your furthest successful sample ended, and",
+            "ends at your first mistake. It is a little longer than the longest of",
+            "your last 10 samples, cut back to whole words, and one word shorter for",
+            "each failure in a row. A sample from where you have typed before races",
+            "a ghost of your best attempt from there. Starting from another place,",
+            "such as a bookmark, makes it your position in the book.",
+        ]
+        .as_slice(),
+        Mode::Timed(_) => [
+            "A timed test starts at your position in the book when you type the first",
+            "key. Mistakes are counted but don't stop you, and the text you type",
+            "correctly counts as progress through the book.",
+        ]
+        .as_slice(),
+        Mode::Drill => [
+            "A drill goes through passages which are dense in the keys and bigrams you",
+            "miss most, retrying a passage until you type it without a mistake.",
+            "Drills don't move your position in the book.",
+        ]
+        .as_slice(),
+    };
+    lines.extend(rules.iter().map(|&rule| Line::from(format!("  {}", rule))));
+
+    lines.push(Line::default());
+    lines.push(heading("Colors"));
+    let theme = app.theme;
+    for (style, sample, meaning) in [
+        (theme.typed, "typed", "text of the sample you have typed"),
+        (theme.current, "c", "character to type next"),
+        (theme.pending, "to type", "rest of the sample"),
+        (theme.context, "context", "text around the sample"),
+        (theme.ghost, "g", "where the ghost of your best attempt is"),
+        (theme.mistake, "m", "where a sample failed, with the key typed above"),
+    ] {
+        lines.push(Line::from(vec![
+            Span::raw("  "),
+            Span::styled(sample, style),
+            Span::raw(format!(
+                "{:width$}{}",
+                "",
+                meaning,
+                width = HELP_KEYS_WIDTH - sample.len()
+            )),
+        ]));
+    }
+
+    let area = centered_rect(HELP_WIDTH, lines.len() as u16 + 2, frame.size());
+    let scroll = u16::min(
+        app.help_scroll,
+        (lines.len() as u16 + 2).saturating_sub(area.height),
+    );
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(lines).scroll((scroll, 0)).block(
+            Block::default()
+                .title("Help")
+                .borders(Borders::ALL)
+                .border_style(app.theme.border),
+        ),
+        area,
+    );
+}
+
+fn render_inspection(app: &App, frame: &mut Frame) {
+    let Some(inspection) = &app.inspection else {
+        return;
+    };
+    let times = |n: usize| if n == 1 { "1 time".to_owned() } else { format!("{} times", n) };
+    let mut lines = vec![Line::from(format!(
+        "Typed {}, failed {}",
+        times(inspection.typed),
+        times(inspection.failed)
+    ))];
+    if !inspection.recent.is_empty() {
+        lines.push(Line::default());
+    }
+    for outcome in &inspection.recent {
+        let result = match outcome.result {
+            WordResult::Typed => "typed".set_style(app.theme.typed),
+            WordResult::Failed(index) => format!(
+                "failed at {:?}",
+                inspection.word[index - inspection.start_index..]
+                    .chars()
+                    .next()
+                    .unwrap_or(' ')
+            )
+            .set_style(app.theme.failed),
+        };
+        lines.push(Line::from(vec![
+            Span::raw(format!(
+                "{}  {:6} ",
+                outcome
+                    .completed
+                    .with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M"),
+                if outcome.timed { "timed" } else { "sample" },
+            )),
+            result,
+            Span::raw(
+                outcome
+                    .wpm
+                    .map(|wpm| format!("  {:.0} WPM", wpm))
+                    .unwrap_or_default(),
+            ),
+        ]));
+    }
+
+    let area = centered_rect(60, lines.len() as u16 + 2, frame.size());
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(lines).block(
+            Block::default()
+                .title(format!(
+                    "{:?} at {}",
+                    inspection.word, inspection.start_index
+                ))
+                .borders(Borders::ALL)
+                .border_style(app.theme.border),
+        ),
+        area,
+    );
 }
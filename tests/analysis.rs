@@ -0,0 +1,100 @@
+use booktyping::analysis::{keypress_placements, ngram_stats, NgramStats, PAUSE_THRESHOLD_MS};
+use booktyping::log::{KeyPress, Test};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+
+/// Time some milliseconds into the logs.
+fn at(ms: i64) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap() + Duration::milliseconds(ms)
+}
+
+fn press(key: char, correct: bool, ms: i64) -> KeyPress {
+    KeyPress {
+        correct,
+        key,
+        expected: None,
+        time: at(ms),
+    }
+}
+
+/// Correct keypresses typing `text`, each at the time paired with it.
+fn typed(text: &str, times: &[i64]) -> Vec<KeyPress> {
+    text.chars()
+        .zip(times)
+        .map(|(key, &ms)| press(key, true, ms))
+        .collect()
+}
+
+fn sample(start_index: usize, end_index: usize, started: i64, completed: i64) -> Test {
+    Test {
+        succeeded: false,
+        start_index,
+        end_index,
+        started: at(started),
+        completed: at(completed),
+        wpm: None,
+        accuracy: None,
+        pauses: Vec::new(),
+        mistyped: None,
+    }
+}
+
+fn find<'a>(stats: &'a [NgramStats], ngram: &str) -> &'a NgramStats {
+    stats.iter().find(|s| s.ngram == ngram).unwrap()
+}
+
+#[test]
+fn mistake_logged_after_failed_sample_is_placed_in_it() {
+    let mut keypresses = typed("abc", &[100, 200, 300]);
+    // The sample is logged as completed before the key which failed it.
+    keypresses.push(press('x', false, 400));
+    keypresses.push(press('d', true, 600));
+    let tests = [sample(0, 3, 0, 350), sample(3, 10, 500, 1000)];
+
+    let placements = keypress_placements(&keypresses, &tests, &[], &[]);
+    let indices = placements
+        .iter()
+        .map(|p| p.map(|p| (p.index, p.start_index)))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        indices,
+        [
+            Some((0, 0)),
+            Some((1, 0)),
+            Some((2, 0)),
+            Some((3, 0)),
+            Some((3, 3))
+        ]
+    );
+}
+
+#[test]
+fn pause_clears_ngram_context() {
+    let keypresses = typed(
+        "abab",
+        &[
+            0,
+            PAUSE_THRESHOLD_MS,
+            PAUSE_THRESHOLD_MS + 100,
+            PAUSE_THRESHOLD_MS + 200,
+        ],
+    );
+    let stats = ngram_stats(&keypresses, 2);
+    assert_eq!(find(&stats, "ab").attempts, 1);
+    assert_eq!(find(&stats, "ab").median_latency_ms, Some(100.0));
+    assert_eq!(find(&stats, "ba").attempts, 1);
+}
+
+#[test]
+fn median_latency_of_odd_and_even_counts() {
+    // "ab" typed with latencies of 100, 600 and 200ms, a second apart.
+    let odd = typed("ababab", &[0, 100, 1000, 1600, 2000, 2200]);
+    let odd_stats = ngram_stats(&odd, 2);
+    assert_eq!(find(&odd_stats, "ab").median_latency_ms, Some(200.0));
+    assert_eq!(find(&odd_stats, "ab").mean_latency_ms, Some(300.0));
+
+    // And once more with 400ms.
+    let even = typed("abababab", &[0, 100, 1000, 1600, 2000, 2200, 3000, 3400]);
+    let even_stats = ngram_stats(&even, 2);
+    assert_eq!(find(&even_stats, "ab").attempts, 4);
+    assert_eq!(find(&even_stats, "ab").median_latency_ms, Some(300.0));
+}
@@ -0,0 +1,52 @@
+use booktyping::drill::{find_passages, Targets, PASSAGE_LEN};
+
+/// Book of plain filler with a few words which are dense in `z`.
+fn book() -> String {
+    let filler = "the cat sat on a mat ".repeat(20);
+    format!("{filler}fizz buzz {filler}jazz fuzz quiz zoo zap {filler}")
+}
+
+fn zs(book: &str, (start, len): (usize, usize)) -> usize {
+    book[start..start + len].matches('z').count()
+}
+
+#[test]
+fn passages_densest_in_targets_come_first() {
+    let book = book();
+    let targets = Targets {
+        keys: vec!['z'],
+        bigrams: Vec::new(),
+    };
+    let passages = find_passages(&book, &targets, 3);
+
+    assert_eq!(passages.len(), 3);
+    assert_eq!(zs(&book, passages[0]), 7);
+    assert_eq!(zs(&book, passages[1]), 4);
+    assert_eq!(zs(&book, passages[2]), 0);
+    for (i, &(start, len)) in passages.iter().enumerate() {
+        assert!(len <= PASSAGE_LEN);
+        // Passages start at a word and end after the space before the next.
+        assert!(start == 0 || &book[start - 1..start] == " ");
+        assert!(book[..start + len].ends_with(' '));
+        for &(other_start, other_len) in &passages[..i] {
+            assert!(start + len <= other_start || other_start + other_len <= start);
+        }
+    }
+}
+
+#[test]
+fn short_passages_are_ranked_by_density() {
+    // The last passage is cut short by the end of the book.
+    let book = format!("{}zz zz end", book());
+    let targets = Targets {
+        keys: Vec::new(),
+        bigrams: vec!["zz".to_owned()],
+    };
+    let passages = find_passages(&book, &targets, 2);
+
+    // As many "zz" as the longer passages, in fewer characters.
+    assert_eq!(passages[0], (book.len() - 9, 6));
+    let (start, len) = passages[1];
+    assert_eq!(book[start..start + len].matches("zz").count(), 2);
+    assert!(len > 6);
+}
@@ -0,0 +1,58 @@
+use booktyping::app::Mode;
+use booktyping::export::{self, Options};
+use booktyping::goals::local_date;
+use booktyping::harness::{start_time, Harness, BOOK_TITLE};
+use chrono::Duration;
+use std::fs;
+
+const BOOK: &str = "It was a bright cold day in April, and the clocks were striking \
+    thirteen. Winston Smith, his chin nuzzled into his breast in an effort to escape \
+    the vile wind, slipped quickly through the glass doors of Victory Mansions.";
+
+#[test]
+fn rows_are_limited_to_the_date_range() {
+    let mut harness = Harness::new(BOOK, Mode::Samples, 60, 14).unwrap();
+    for attempt in ["It wax", "It was x", "It x"] {
+        for c in attempt.chars() {
+            harness.type_text(&c.to_string()).unwrap();
+            harness.advance(Duration::milliseconds(100)).unwrap();
+        }
+        harness.advance(Duration::days(1)).unwrap();
+    }
+
+    let day = local_date(start_time() + Duration::days(1));
+    let options = Options {
+        from: Some(day),
+        to: Some(day),
+        out_dir: harness.library().join("export"),
+    };
+    export::run(&harness.app.logs, BOOK_TITLE, &options).unwrap();
+
+    let read = |kind: &str| {
+        fs::read_to_string(options.out_dir.join(format!("{}-{}.csv", BOOK_TITLE, kind))).unwrap()
+    };
+    let started = start_time() + Duration::days(1) + Duration::milliseconds(600);
+    let completed = started + Duration::milliseconds(700);
+    assert_eq!(
+        read("tests"),
+        format!(
+            "kind,started,completed,start_index,end_index,length,duration_seconds,wpm,accuracy,succeeded\n\
+            sample,{},{},0,7,7,0.700,120.00,0.8750,false\n",
+            started.format("%Y-%m-%dT%H:%M:%S%.3fZ"),
+            completed.format("%Y-%m-%dT%H:%M:%S%.3fZ"),
+        )
+    );
+    let keypresses = read("keypresses");
+    let rows = keypresses.lines().collect::<Vec<_>>();
+    assert_eq!(rows[0], "time,interval_ms,key,expected,correct,position");
+    assert_eq!(rows.len(), 1 + "It was x".len());
+    // The interval of the first keypress of the day is from the day before.
+    assert_eq!(
+        rows[1],
+        format!(
+            "{},{},I,I,true,0",
+            started.format("%Y-%m-%dT%H:%M:%S%.3fZ"),
+            Duration::days(1).num_milliseconds() + 100
+        )
+    );
+}
@@ -0,0 +1,46 @@
+use booktyping::config::Goal;
+use booktyping::goals::{practiced, Activity, DayTotals, GoalReport};
+use chrono::NaiveDate;
+
+fn date(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
+}
+
+/// Activity with the given minutes of practice on days of January.
+fn activity(days: &[(u32, f64)]) -> Activity {
+    Activity {
+        days: days
+            .iter()
+            .map(|&(day, minutes)| {
+                let totals = DayTotals {
+                    minutes,
+                    ..DayTotals::default()
+                };
+                (date(day), totals)
+            })
+            .collect(),
+    }
+}
+
+#[test]
+fn streaks_are_broken_by_a_missed_day() {
+    let activity = activity(&[(1, 5.0), (2, 5.0), (4, 5.0), (5, 5.0), (6, 5.0), (8, 5.0)]);
+
+    assert_eq!(activity.current_streak(date(6), practiced), 3);
+    // Nothing done yet today keeps yesterday's streak going.
+    assert_eq!(activity.current_streak(date(7), practiced), 3);
+    assert_eq!(activity.current_streak(date(8), practiced), 1);
+    assert_eq!(activity.current_streak(date(10), practiced), 0);
+    assert_eq!(activity.longest_streak(practiced), 3);
+}
+
+#[test]
+fn goal_streak_counts_days_meeting_the_goal() {
+    let activity = activity(&[(1, 20.0), (2, 5.0), (3, 20.0), (4, 30.0), (5, 10.0)]);
+    let report = GoalReport::new(&activity, Some(Goal::Minutes(15)), date(5));
+
+    assert_eq!(report.today, Some(10.0));
+    assert_eq!(report.goal_streak, Some(2));
+    assert_eq!(report.current_streak, 5);
+    assert_eq!(report.longest_streak, 5);
+}
@@ -0,0 +1,302 @@
+use booktyping::app::Mode;
+use booktyping::event::Event;
+use booktyping::ghost::Ghosts;
+use booktyping::harness::{start_time, Harness, BOOK_TITLE};
+use booktyping::log::Bookmark;
+use booktyping::record::{Header, Recorder, Recording};
+use booktyping::theme::Theme;
+use chrono::Duration;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::style::Color;
+
+const BOOK: &str = "It was a bright cold day in April, and the clocks were striking \
+    thirteen. Winston Smith, his chin nuzzled into his breast in an effort to escape \
+    the vile wind, slipped quickly through the glass doors of Victory Mansions.";
+
+#[test]
+fn first_frame() {
+    let harness = Harness::new(BOOK, Mode::Samples, 60, 14).unwrap();
+    harness.assert_snapshot("first_frame");
+}
+
+#[test]
+fn mistake_ends_sample() {
+    let mut harness = Harness::new(BOOK, Mode::Samples, 60, 14).unwrap();
+    harness.type_text("It was").unwrap();
+    assert_eq!(harness.app.cur_char, 6);
+    harness.type_text("x").unwrap();
+
+    let test = harness.app.tests.last().unwrap();
+    assert!(!test.succeeded);
+    assert_eq!((test.start_index, test.end_index), (0, 6));
+    assert_eq!(harness.app.cur_char, 0);
+    assert_eq!(harness.app.logs.read_tests(BOOK_TITLE).unwrap().len(), 1);
+    harness.assert_snapshot("mistake_ends_sample");
+}
+
+#[test]
+fn mistake_is_shown_until_next_key() {
+    let mut harness = Harness::new(BOOK, Mode::Samples, 60, 14).unwrap();
+    harness.type_text("It wax").unwrap();
+
+    assert_eq!(harness.app.tests[0].mistyped, Some('x'));
+    assert_eq!(
+        harness.app.logs.read_tests(BOOK_TITLE).unwrap()[0].mistyped,
+        Some('x')
+    );
+    assert_eq!(harness.app.shown_mistake(), Some((5, 'x')));
+    // The failure is on the top line, so what was typed is shown below it.
+    let (x, y) = (harness.app.text_area.x + 5, harness.app.text_area.y);
+    assert_eq!(harness.buffer().get(x, y).bg, Color::Red);
+    assert_eq!(harness.buffer().get(x, y + 1).symbol, "x");
+    harness.advance(Duration::milliseconds(1000)).unwrap();
+    assert!(harness.app.shown_mistake().is_some());
+
+    harness.type_text("I").unwrap();
+    assert!(harness.app.shown_mistake().is_none());
+    assert_ne!(harness.buffer().get(x, y).bg, Color::Red);
+    harness.type_text("x").unwrap();
+    harness.advance(Duration::milliseconds(1500)).unwrap();
+    assert!(harness.app.shown_mistake().is_none());
+}
+
+#[test]
+fn search_is_not_typing() {
+    let mut harness = Harness::new(BOOK, Mode::Samples, 60, 14).unwrap();
+    harness.ctrl('s').unwrap();
+    harness.type_text("victory").unwrap();
+    harness.key(KeyCode::Enter).unwrap();
+
+    assert_eq!(harness.app.cur_char, 0);
+    assert!(harness.app.tests.is_empty());
+    assert!(harness
+        .app
+        .logs
+        .read_keypresses(BOOK_TITLE)
+        .unwrap()
+        .is_empty());
+    harness.assert_snapshot("search_is_not_typing");
+}
+
+#[test]
+fn idle_time_is_left_out() {
+    let mut harness = Harness::new(BOOK, Mode::Samples, 60, 14).unwrap();
+    harness.type_text("It").unwrap();
+    harness.advance(Duration::seconds(1)).unwrap();
+    harness.type_text(" w").unwrap();
+    harness.advance(Duration::minutes(5)).unwrap();
+    assert!(harness.app.paused_since.is_some());
+    harness.type_text("ax").unwrap();
+
+    let test = harness.app.tests.last().unwrap();
+    assert_eq!(test.pauses.len(), 1);
+    assert_eq!(test.duration(), Duration::seconds(1));
+}
+
+#[test]
+fn timed_test_ends_when_time_runs_out() {
+    let mut harness = Harness::new(BOOK, Mode::Timed(Duration::minutes(1)), 60, 14).unwrap();
+    // Type a key every 3 seconds, so that the clock is never paused.
+    for (i, c) in BOOK.chars().take(30).enumerate() {
+        if i > 0 {
+            harness.advance(Duration::seconds(3)).unwrap();
+        }
+        harness.type_text(&c.to_string()).unwrap();
+    }
+
+    let result = harness.app.timed_result.as_ref().unwrap();
+    assert_eq!(result.end_index, 20);
+    assert_eq!(result.duration(), Duration::minutes(1));
+    harness.assert_snapshot("timed_test_ends_when_time_runs_out");
+}
+
+#[test]
+fn recording_replays_the_same_run() {
+    let mut harness = Harness::new(BOOK, Mode::Samples, 60, 14).unwrap();
+    let header = Header::new(
+        &harness.app.logs,
+        vec![BOOK_TITLE.to_owned()],
+        BOOK_TITLE,
+        (60, 14),
+        start_time(),
+    )
+    .unwrap();
+    let path = harness.library().join("recording.json");
+    let mut recorder = Recorder::create(&path, &header).unwrap();
+    for (i, c) in "It was a bright cold dax".chars().enumerate() {
+        let event = Event::Key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+        harness
+            .advance(Duration::milliseconds(150 + i as i64 * 7))
+            .unwrap();
+        recorder.record(harness.app.clock.now(), event).unwrap();
+        harness.event(event).unwrap();
+    }
+
+    let recording = Recording::load(&path).unwrap();
+    assert_eq!(recording.entries.len(), 24);
+    let replayed = Harness::replay(&recording).unwrap();
+    assert_eq!(replayed.screen(), harness.screen());
+    assert_eq!(replayed.app.tests.len(), 1);
+    assert_eq!(
+        replayed.app.tests[0].duration(),
+        harness.app.tests[0].duration()
+    );
+}
+
+#[test]
+fn charts_fit_short_terminals() {
+    for height in 6..=11 {
+        let mut harness = Harness::new(BOOK, Mode::Samples, 60, height).unwrap();
+        harness.key(KeyCode::F(4)).unwrap();
+        harness.type_text("It was").unwrap();
+        assert_eq!(harness.app.cur_char, 6, "height {}", height);
+    }
+}
+
+#[test]
+fn ghost_from_logs_leaves_out_pauses() {
+    let mut harness = Harness::new(BOOK, Mode::Samples, 60, 14).unwrap();
+    let type_slowly = |harness: &mut Harness, text: &str| {
+        for c in text.chars() {
+            harness.advance(Duration::milliseconds(100)).unwrap();
+            harness.type_text(&c.to_string()).unwrap();
+        }
+    };
+    type_slowly(&mut harness, "It");
+    harness.key(KeyCode::F(6)).unwrap();
+    harness.advance(Duration::seconds(60)).unwrap();
+    type_slowly(&mut harness, " wax");
+
+    let ghosts = Ghosts::from_logs(
+        &harness.app.logs.read_keypresses(BOOK_TITLE).unwrap(),
+        &harness.app.logs.read_tests(BOOK_TITLE).unwrap(),
+        &[],
+    );
+    let times = ghosts.get(0).unwrap().times.iter();
+    let times = times.map(Duration::num_milliseconds).collect::<Vec<_>>();
+    // The pause ends with the space, as it does for a live attempt.
+    assert_eq!(times, [0, 100, 100, 200, 300]);
+}
+
+#[test]
+fn jumping_back_keeps_progress_made_before() {
+    let mut harness = Harness::new(BOOK, Mode::Samples, 60, 14).unwrap();
+    // Types the expected characters until the position has moved on.
+    let advance_to = |harness: &mut Harness, position: usize| {
+        while harness.app.progress.position < position {
+            let index = harness.app.sample_start_index + harness.app.cur_char;
+            harness.advance(Duration::milliseconds(100)).unwrap();
+            harness.type_text(&BOOK[index..index + 1]).unwrap();
+        }
+        harness.app.progress.position
+    };
+    let first = advance_to(&mut harness, 30);
+    harness.app.start_practicing(0).unwrap();
+    assert_eq!(harness.app.progress.position, 0);
+    let second = advance_to(&mut harness, 10);
+
+    harness.app.end_session().unwrap();
+    let summary = harness.app.summary.as_ref().unwrap();
+    assert_eq!(summary.chars_advanced, first + second);
+}
+
+#[test]
+fn drills_are_kept_apart_from_the_book() {
+    let mut harness = Harness::new(BOOK, Mode::Samples, 60, 14).unwrap();
+    for c in BOOK[..60].chars() {
+        harness.advance(Duration::milliseconds(100)).unwrap();
+        harness.type_text(&c.to_string()).unwrap();
+    }
+    let tests = harness.app.tests.clone();
+    let speed = harness.app.get_rolling_speed();
+
+    harness.restart(Mode::Drill).unwrap();
+    harness.advance(Duration::milliseconds(100)).unwrap();
+    // The book has no tildes, so this fails the first drill.
+    harness.type_text("~").unwrap();
+    assert_eq!(harness.app.drills.len(), 1);
+    assert_eq!(harness.app.tests.len(), tests.len());
+    assert_eq!(harness.app.get_rolling_speed(), speed);
+    assert_eq!(
+        harness.app.logs.read_tests(BOOK_TITLE).unwrap().len(),
+        tests.len()
+    );
+    assert_eq!(harness.app.logs.read_drills(BOOK_TITLE).unwrap().len(), 1);
+
+    harness.app.end_session().unwrap();
+    assert_eq!(harness.app.summary.as_ref().unwrap().samples, 1);
+}
+
+#[test]
+fn panels_use_the_theme() {
+    let mut harness = Harness::new(BOOK, Mode::Samples, 60, 14).unwrap();
+    harness.type_text("It wax").unwrap();
+    harness.app.theme = Theme::built_in("light").unwrap();
+    harness.key(KeyCode::F(4)).unwrap();
+    let buffer = harness.buffer();
+    let colors = |rows: std::ops::Range<u16>| {
+        rows.flat_map(|y| (1..59).map(move |x| (buffer.get(x, y).fg, buffer.get(x, y).bg)))
+            .collect::<Vec<_>>()
+    };
+    // The progress gauge above the bottom border is still empty.
+    assert!(colors(12..13)
+        .iter()
+        .all(|&colors| colors == (Color::White, Color::Blue)));
+    // The failed sample and the accuracy line in the charts.
+    let charts = colors(9..12);
+    assert!(charts.contains(&(Color::Red, Color::Reset)));
+    assert!(charts.contains(&(Color::Green, Color::Reset)));
+}
+
+#[test]
+fn bookmark_past_the_end_goes_to_the_end() {
+    let mut harness = Harness::new(BOOK, Mode::Samples, 60, 14).unwrap();
+    // As if the book was shortened after the bookmark was added.
+    harness.app.bookmarks.push(Bookmark {
+        name: "gone".to_owned(),
+        index: BOOK.len() + 100,
+        created: start_time(),
+    });
+    harness.key(KeyCode::F(5)).unwrap();
+    harness.key(KeyCode::Enter).unwrap();
+    assert_eq!(
+        harness.app.scrolled_index(),
+        Some(BOOK.rfind(' ').unwrap() + 1)
+    );
+}
+
+#[test]
+fn quitting_logs_the_timed_test_so_far() {
+    let mut harness = Harness::new(BOOK, Mode::Timed(Duration::minutes(1)), 60, 14).unwrap();
+    for c in BOOK.chars().take(10) {
+        harness.type_text(&c.to_string()).unwrap();
+        harness.advance(Duration::seconds(1)).unwrap();
+    }
+    harness.app.end_session().unwrap();
+
+    let timed_tests = harness.app.logs.read_timed_tests(BOOK_TITLE).unwrap();
+    assert_eq!(timed_tests.len(), 1);
+    assert_eq!(timed_tests[0].end_index, 10);
+    assert_eq!(timed_tests[0].duration(), Duration::seconds(10));
+    let summary = harness.app.summary.as_ref().unwrap();
+    assert_eq!(summary.practice_seconds, 10);
+    assert_eq!(summary.chars_advanced, 10);
+}
+
+#[test]
+fn sample_is_timed_from_the_first_keypress() {
+    let mut harness = Harness::new(BOOK, Mode::Samples, 60, 14).unwrap();
+    harness.advance(Duration::seconds(5)).unwrap();
+    harness.type_text("It").unwrap();
+    let first_keypress = harness.app.clock.now();
+    for c in " was a".chars() {
+        harness.advance(Duration::seconds(1)).unwrap();
+        harness.type_text(&c.to_string()).unwrap();
+    }
+    harness.type_text("x").unwrap();
+
+    let test = harness.app.tests.last().unwrap();
+    assert_eq!(test.started, first_keypress);
+    assert_eq!(test.duration(), Duration::seconds(6));
+    assert_eq!(test.wpm, Some(8.0 / 5.0 / 0.1));
+}
@@ -0,0 +1,63 @@
+use booktyping::config::Config;
+use booktyping::keymap::{Action, Keymap};
+
+/// Error from building the keymap with some actions bound to other keys.
+fn error(keys: &[(Action, &[&str])]) -> String {
+    let mut config = Config::default();
+    for &(action, names) in keys {
+        config
+            .keys
+            .insert(action, names.iter().map(|&name| name.to_owned()).collect());
+    }
+    Keymap::from_config(&config).unwrap_err().to_string()
+}
+
+#[test]
+fn default_keys_are_valid() {
+    assert_eq!(
+        Keymap::from_config(&Config::default()).unwrap(),
+        Keymap::default()
+    );
+}
+
+#[test]
+fn invalid_key_names_are_reported() {
+    let error = error(&[(Action::Help, &["f1", "ctrl-"]), (Action::Pause, &["f 6"])]);
+    assert!(error.contains("Invalid key name: ctrl-"), "{}", error);
+    assert!(error.contains("Invalid key name: f 6"), "{}", error);
+}
+
+#[test]
+fn conflicts_are_reported() {
+    let error = error(&[(Action::Charts, &["f2"])]);
+    assert!(
+        error.contains("F2 is bound to both heatmap and charts"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn scrolling_conflicts_with_overlays() {
+    // The scrolling keys move through the bookmarks while they are open.
+    let error = error(&[(Action::ScrollDown, &["delete"])]);
+    assert!(
+        error.contains("Delete is bound to both scroll_down and delete_bookmark"),
+        "{}",
+        error
+    );
+    // Other keys of the text are free to be used again over overlays.
+    let mut config = Config::default();
+    config.keys.insert(Action::Charts, vec!["tab".to_owned()]);
+    assert!(Keymap::from_config(&config).is_ok());
+}
+
+#[test]
+fn typing_keys_are_refused_in_the_text() {
+    let error = error(&[(Action::Search, &["s"])]);
+    assert!(
+        error.contains("s is bound to search, which stops it being typed"),
+        "{}",
+        error
+    );
+}
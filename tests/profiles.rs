@@ -0,0 +1,132 @@
+use booktyping::app::Mode;
+use booktyping::harness::{Harness, BOOK_TITLE};
+use booktyping::profile::{self, Standing};
+use crossterm::event::KeyCode;
+
+const BOOK: &str = "It was a bright cold day in April, and the clocks were striking \
+    thirteen. Winston Smith, his chin nuzzled into his breast in an effort to escape \
+    the vile wind, slipped quickly through the glass doors of Victory Mansions.";
+
+#[test]
+fn profiles_keep_their_own_logs() {
+    let mut harness = Harness::new(BOOK, Mode::Samples, 80, 16).unwrap();
+    harness.type_text("It wax").unwrap();
+
+    harness.key(KeyCode::F(7)).unwrap();
+    harness.type_text("ann").unwrap();
+    harness.key(KeyCode::Enter).unwrap();
+    assert_eq!(harness.app.logs.profile.as_deref(), Some("ann"));
+    assert!(harness.app.profile_picker.is_none());
+    assert!(harness.app.tests.is_empty());
+    harness.type_text("It was a bright coll").unwrap();
+
+    let tests = |profile: Option<&str>| {
+        let logs = harness.app.logs.with_profile(profile.map(str::to_owned));
+        logs.read_tests(BOOK_TITLE).unwrap()
+    };
+    assert_eq!(tests(None)[0].end_index, 5);
+    assert_eq!(tests(Some("ann"))[0].end_index, 19);
+    // The session of the default profile was saved when switching from it.
+    let sessions = harness
+        .app
+        .logs
+        .with_profile(None)
+        .read_sessions(BOOK_TITLE)
+        .unwrap();
+    assert_eq!(sessions.len(), 1);
+
+    // Switch back to the default profile from the picker.
+    harness.key(KeyCode::F(7)).unwrap();
+    harness.assert_snapshot("profile_picker");
+    harness.key(KeyCode::Up).unwrap();
+    harness.key(KeyCode::Enter).unwrap();
+    assert_eq!(harness.app.logs.profile, None);
+    assert_eq!(harness.app.tests.len(), 1);
+}
+
+#[test]
+fn profiles_are_compared() {
+    let mut harness = Harness::new(BOOK, Mode::Samples, 80, 16).unwrap();
+    harness.app.switch_profile(Some("bob".to_owned())).unwrap();
+    harness.type_text(&BOOK[..12]).unwrap();
+    harness.type_text("x").unwrap();
+
+    let standings = profile::compare(&harness.app.logs, BOOK_TITLE).unwrap();
+    let names = standings
+        .iter()
+        .map(|standing| profile::name(&standing.profile))
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["default", "bob"]);
+    assert_eq!(standings[0].samples, 0);
+    assert_eq!(standings[1].samples, 1);
+    assert!((standings[1].accuracy.unwrap() - 12.0 / 13.0).abs() < 1e-9);
+    assert_eq!(
+        Standing::load(&harness.app.logs.with_profile(None), BOOK_TITLE)
+            .unwrap()
+            .last_practiced,
+        None
+    );
+}
+
+#[test]
+fn failed_switch_keeps_current_profile() {
+    let mut harness = Harness::new(BOOK, Mode::Samples, 80, 16).unwrap();
+    let dir = harness.library().join("profiles/zed");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("config.json"), "{").unwrap();
+    harness.type_text("It w").unwrap();
+
+    harness.key(KeyCode::F(7)).unwrap();
+    harness.type_text("zed").unwrap();
+    harness.key(KeyCode::Enter).unwrap();
+    let picker = harness.app.profile_picker.as_ref().unwrap();
+    assert!(picker
+        .error
+        .as_ref()
+        .unwrap()
+        .contains("Invalid config file"));
+    assert_eq!(harness.app.logs.profile, None);
+
+    // Nothing was saved or lost, and typing carries on.
+    harness.key(KeyCode::Esc).unwrap();
+    harness.type_text("ax").unwrap();
+    assert_eq!(harness.app.tests[0].end_index, 5);
+    assert!(harness
+        .app
+        .logs
+        .read_sessions(BOOK_TITLE)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn invalid_profile_names_are_refused() {
+    assert_eq!(profile::from_name("default"), Ok(None));
+    assert_eq!(profile::from_name(" ann "), Ok(Some("ann".to_owned())));
+    assert!(profile::from_name("../ann").is_err());
+    assert!(profile::from_name(".hidden").is_err());
+    assert!(profile::from_name("").is_err());
+
+    let mut harness = Harness::new(BOOK, Mode::Samples, 80, 16).unwrap();
+    harness.key(KeyCode::F(7)).unwrap();
+    harness.type_text("a/b").unwrap();
+    harness.key(KeyCode::Enter).unwrap();
+    let picker = harness.app.profile_picker.as_ref().unwrap();
+    assert!(picker.error.is_some());
+    assert_eq!(harness.app.logs.profile, None);
+}
+
+#[test]
+fn clicks_are_ignored_while_picking_a_profile() {
+    let mut harness = Harness::new(BOOK, Mode::Samples, 80, 16).unwrap();
+    harness.type_text("It wax").unwrap();
+
+    harness.key(KeyCode::F(7)).unwrap();
+    for row in 0..16 {
+        for column in 0..80 {
+            harness.click(column, row).unwrap();
+            assert!(harness.app.overlay.is_none());
+        }
+    }
+    assert!(harness.app.profile_picker.is_some());
+}
@@ -0,0 +1,24 @@
+use booktyping::progress::find_chapters;
+
+#[test]
+fn chapters_are_found_by_their_headings() {
+    let text =
+        "PREFACE Some words. Chapter IV The storm began. CHAPTER 5. Calm. Chapter Six Quiet.";
+    let chapters = find_chapters(text);
+    assert_eq!(
+        chapters,
+        [
+            (20, "Chapter IV".to_owned()),
+            (48, "CHAPTER 5".to_owned()),
+            (65, "Chapter Six".to_owned()),
+        ]
+    );
+}
+
+#[test]
+fn chapter_mid_sentence_is_not_a_heading() {
+    let text = "Chapter I It began, as told in Chapter IV of the diary, long ago. \
+        Later, Chapter II came, and then the final Chapter Three.";
+    let chapters = find_chapters(text);
+    assert_eq!(chapters, [(0, "Chapter I".to_owned())]);
+}
@@ -0,0 +1,76 @@
+use booktyping::app::Mode;
+use booktyping::harness::{Harness, BOOK_TITLE};
+use booktyping::replay::{Replay, Window};
+use chrono::Duration;
+
+const BOOK: &str = "It was a bright cold day in April, and the clocks were striking \
+    thirteen. Winston Smith, his chin nuzzled into his breast in an effort to escape \
+    the vile wind, slipped quickly through the glass doors of Victory Mansions.";
+
+/// Replay of "It was", then " a" and a mistake after a break.
+fn replay() -> Replay {
+    let mut harness = Harness::new(BOOK, Mode::Samples, 60, 14).unwrap();
+    for text in ["It was", " ax"] {
+        for c in text.chars() {
+            harness.type_text(&c.to_string()).unwrap();
+            harness.advance(Duration::milliseconds(100)).unwrap();
+        }
+        harness.advance(Duration::seconds(20)).unwrap();
+    }
+    let window = Window {
+        from: None,
+        to: None,
+        speed: 1.0,
+    };
+    Replay::load(
+        &harness.app.logs,
+        BOOK_TITLE,
+        window,
+        harness.app.clock.now(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn seeking_stays_within_the_replay() {
+    let mut replay = replay();
+    // A second before the first key, then the break cut to five seconds.
+    assert_eq!(
+        replay.length(),
+        Duration::milliseconds(1000 + 500 + 5000 + 200)
+    );
+
+    replay.seek(Duration::seconds(3));
+    assert_eq!(replay.frame().cur_char, 6);
+    replay.seek(Duration::seconds(-10));
+    assert_eq!(replay.position, Duration::zero());
+    assert_eq!(replay.frame().cur_char, 0);
+    replay.seek(Duration::minutes(1));
+    assert_eq!(replay.position, replay.length());
+    assert!(replay.finished());
+    assert_eq!(replay.frame().cur_char, 8);
+}
+
+#[test]
+fn pausing_stops_the_replay() {
+    let mut replay = replay();
+    let start = replay.frame().time;
+
+    replay.toggle_pause();
+    replay.update(start + Duration::seconds(1));
+    assert_eq!(replay.position, Duration::zero());
+    replay.toggle_pause();
+    replay.update(start + Duration::milliseconds(2100));
+    assert_eq!(replay.position, Duration::milliseconds(1100));
+    assert_eq!(replay.frame().cur_char, 2);
+
+    replay.faster();
+    replay.update(start + Duration::milliseconds(2200));
+    assert_eq!(replay.position, Duration::milliseconds(1300));
+
+    // Playing again from the end starts over.
+    replay.skip_to_end();
+    replay.toggle_pause();
+    replay.toggle_pause();
+    assert_eq!(replay.position, Duration::zero());
+}
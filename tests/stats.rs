@@ -0,0 +1,99 @@
+use booktyping::app::Mode;
+use booktyping::goals::local_date;
+use booktyping::harness::Harness;
+use booktyping::log::{Test, TimedTest};
+use booktyping::stats::Report;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+
+const BOOK: &str = "It was a bright cold day in April, and the clocks were striking \
+    thirteen. Winston Smith, his chin nuzzled into his breast in an effort to escape \
+    the vile wind, slipped quickly through the glass doors of Victory Mansions.";
+
+#[test]
+fn missing_book_is_named() {
+    let harness = Harness::new(BOOK, Mode::Samples, 60, 14).unwrap();
+    let error = Report::new(&harness.app.logs, "Missing").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        format!("No book named Missing in {}", harness.library().display())
+    );
+}
+
+/// Time some seconds into a day of the logs.
+fn at(day: u32, seconds: i64) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 1, day, 12, 0, 0).unwrap() + Duration::seconds(seconds)
+}
+
+fn sample(succeeded: bool, start_index: usize, end_index: usize, day: u32, seconds: i64) -> Test {
+    Test {
+        succeeded,
+        start_index,
+        end_index,
+        started: at(day, 0),
+        completed: at(day, seconds),
+        wpm: None,
+        accuracy: None,
+        pauses: Vec::new(),
+        mistyped: None,
+    }
+}
+
+#[test]
+fn report_sums_practice_by_day() {
+    let tests = [
+        sample(true, 0, 20, 1, 10),
+        sample(false, 20, 25, 1, 2),
+        sample(false, 20, 25, 2, 4),
+    ];
+    let timed_tests = [TimedTest {
+        start_index: 20,
+        end_index: 80,
+        keypresses: 62,
+        mistakes: 2,
+        gross_wpm: 12.4,
+        net_wpm: 10.4,
+        accuracy: 60.0 / 62.0,
+        started: at(2, 100),
+        completed: at(2, 160),
+        pauses: Vec::new(),
+    }];
+    let report = Report::from_logs("Book", BOOK, &tests, &timed_tests, &[], &[], Vec::new());
+
+    assert_eq!(report.samples, 3);
+    assert_eq!(report.samples_passed, 1);
+    assert_eq!(report.timed_tests, 1);
+    assert_eq!(report.practice_seconds, 10 + 2 + 4 + 60);
+    let longest = report.longest_success.unwrap();
+    assert_eq!((longest.start_index, longest.length), (0, 20));
+    // Both failed samples stopped at the "i" of "in".
+    assert_eq!(report.most_missed.len(), 1);
+    assert_eq!(report.most_missed[0].character, 'i');
+    assert_eq!(report.most_missed[0].count, 2);
+
+    let days = report
+        .daily
+        .iter()
+        .map(|p| {
+            (
+                p.period.clone(),
+                p.samples,
+                p.samples_passed,
+                p.practice_seconds,
+                p.wpm,
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        days,
+        [
+            (local_date(at(1, 0)).to_string(), 2, 1, 12, Some(25.0)),
+            (
+                local_date(at(2, 0)).to_string(),
+                1,
+                0,
+                64,
+                Some(65.0 / 5.0 / (64.0 / 60.0))
+            ),
+        ]
+    );
+}
@@ -0,0 +1,87 @@
+use booktyping::typing_text::TypingText;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    widgets::Widget,
+};
+
+/// Lines of "one two three four" wrapped at 8 columns, and where each
+/// character is drawn.
+fn wrapped() -> (Vec<String>, Vec<(usize, usize)>) {
+    let lines = vec![
+        "one two ".to_owned(),
+        "three ".to_owned(),
+        "four".to_owned(),
+    ];
+    let mut line_index = Vec::new();
+    for (row, line) in lines.iter().enumerate() {
+        line_index.extend((0..line.len()).map(|column| (row, column)));
+    }
+    (lines, line_index)
+}
+
+fn render(text: TypingText, width: u16, height: u16) -> Buffer {
+    let mut buffer = Buffer::empty(Rect::new(0, 0, width, height));
+    text.render(buffer.area, &mut buffer);
+    buffer
+}
+
+/// Text of each row of a buffer.
+fn rows(buffer: &Buffer) -> Vec<String> {
+    (0..buffer.area.height)
+        .map(|y| {
+            (0..buffer.area.width)
+                .map(|x| buffer.get(x, y).symbol.as_str())
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn ranges_patch_earlier_ranges() {
+    let (lines, line_index) = wrapped();
+    let red = Style::new().fg(Color::Red);
+    let buffer = render(
+        TypingText::new(&lines, &line_index)
+            .style(Style::new().fg(Color::Gray))
+            .range(0..11, red)
+            .range(4..6, Style::new().add_modifier(Modifier::BOLD)),
+        10,
+        3,
+    );
+
+    assert_eq!(rows(&buffer), ["one two   ", "three     ", "four      "]);
+    let style = |x, y| {
+        let cell = buffer.get(x, y);
+        (cell.fg, cell.modifier)
+    };
+    assert_eq!(style(0, 0), (Color::Red, Modifier::empty()));
+    assert_eq!(style(4, 0), (Color::Red, Modifier::BOLD));
+    assert_eq!(style(6, 0), (Color::Red, Modifier::empty()));
+    assert_eq!(style(2, 1), (Color::Red, Modifier::empty()));
+    assert_eq!(style(3, 1), (Color::Gray, Modifier::empty()));
+    assert_eq!(style(0, 2), (Color::Gray, Modifier::empty()));
+    // Only the characters of the text are styled.
+    assert_eq!(style(8, 0), (Color::Reset, Modifier::empty()));
+    assert_eq!(style(4, 2), (Color::Reset, Modifier::empty()));
+}
+
+#[test]
+fn scrolled_and_clipped() {
+    let (lines, line_index) = wrapped();
+    let red = Style::new().fg(Color::Red);
+    let text = TypingText::new(&lines, &line_index).first_line(1);
+    assert_eq!(text.visible(1), 8..14);
+
+    let buffer = render(text.range(0..12, red).range(16..18, red), 4, 1);
+    assert_eq!(rows(&buffer), ["thre"]);
+    assert_eq!(buffer.get(2, 0).fg, Color::Red);
+    assert_eq!(buffer.get(3, 0).fg, Color::Red);
+    assert_eq!(
+        TypingText::new(&lines, &line_index)
+            .first_line(5)
+            .visible(2),
+        18..18
+    );
+}